@@ -1,6 +1,7 @@
 //! Errors
 
-use crate::models::MattermostError;
+use crate::{models::MattermostError, retry::RateLimit};
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors that may arise over the course of using the library.
@@ -15,18 +16,158 @@ pub enum ApiError {
     ReqwestError(#[from] reqwest::Error),
     #[error("HTTP header printing error")]
     ReqwestHeaderError(#[from] reqwest::header::ToStrError),
+    /// Also returned for a successful response whose body doesn't match
+    /// the expected model; earlier versions returned `ReqwestError`.
     #[error("JSON processing error")]
     JsonProcessingError(#[from] serde_json::Error),
     #[error("HTTP header value parsing error")]
     ReqwestHeaderValueError(#[from] reqwest::header::InvalidHeaderValue),
     #[error("Invalid HTTP method")]
     HttpMethodError(#[from] http::method::InvalidMethod),
+    /// An error from the instance API, other than those with their own
+    /// variants: 401 responses are `Unauthorized`, 403 `Forbidden`, 404
+    /// `NotFound`, and 429 `RateLimited`.
     #[error("Mattermost API returned error: {0:?}")]
     MattermostApiError(MattermostError),
+    #[error("Not authenticated: {0:?}")]
+    Unauthorized(MattermostError),
+    #[error("Missing permissions: {0:?}")]
+    Forbidden(MattermostError),
+    #[error("Not found: {0:?}")]
+    NotFound(MattermostError),
+    /// The resource hasn't changed since the version with the ETag sent
+    /// in `If-None-Match`. Use `Mattermost::query_raw_with_headers` to
+    /// handle this as a response rather than an error.
+    #[error("Not modified")]
+    NotModified,
     #[error("Non-standard remote status code error")]
     StatusCodeError(u16),
     #[error("Websocket connection error")]
     WebsocketError(#[from] Box<async_tungstenite::tungstenite::Error>),
     #[error("Url parsing error")]
     UrlError(#[from] url::ParseError),
+    #[error("Unknown or invalid usernames: {0:?}")]
+    UnknownUsernames(Vec<String>),
+    #[error("Invalid post priority: {0}")]
+    InvalidPostPriority(&'static str),
+    /// A preference field is over the server's length limit, counted
+    /// in bytes for the category and name and characters for the value.
+    #[error("Preference {field} has length {actual}, over the limit of {limit}")]
+    PreferenceTooLong {
+        field: &'static str,
+        limit: usize,
+        actual: usize,
+    },
+    #[error("Preference category is empty")]
+    EmptyPreferenceCategory,
+    #[error("Server config section {0} did not match what was sent after patching")]
+    ConfigPatchMismatch(&'static str),
+    #[error("Cannot connect to a websocket with a {0} URL")]
+    UnsupportedWebsocketScheme(String),
+    #[error("Cannot use a proxy with a {0} URL")]
+    UnsupportedProxyScheme(String),
+    #[error("Could not connect through the websocket proxy: {0}")]
+    WebsocketProxyError(String),
+    /// The websocket API rejected the authentication challenge, with its
+    /// error if it sent one, or closed or didn't reply to it in time.
+    /// With `AuthMode::Cookie`, also when the upgrade request is rejected
+    /// as unauthorized.
+    #[error("Websocket authentication failed: {0:?}")]
+    WebsocketAuthenticationFailed(Option<Box<MattermostError>>),
+    #[error("The websocket connection is closed")]
+    WebsocketClosed,
+    /// Keys in a response that the model it was deserialized into drops,
+    /// as paths like "metadata.embeds.0.data". Only returned with the
+    /// `strict-models` feature.
+    #[error("Response has fields the model drops: {0:?}")]
+    UnknownFields(Vec<String>),
+    /// A response body was larger than the limit set with
+    /// `Mattermost::with_max_response_size`. When the server didn't send
+    /// the size up front, `actual` is how much was read before stopping.
+    #[error("Response is {actual} bytes, over the limit of {limit}")]
+    ResponseTooLarge { limit: u64, actual: u64 },
+    #[error("Rate limited by the server: {rate_limit:?}")]
+    RateLimited {
+        /// The error from the response body, if it had one.
+        error: Option<Box<MattermostError>>,
+        /// How long the server asked to wait before trying again,
+        /// from its `Retry-After` header.
+        retry_after: Option<Duration>,
+        /// Information from the rate limiting headers.
+        rate_limit: RateLimit,
+    },
+}
+
+impl ApiError {
+    /// The error from the instance API, if this is one.
+    pub fn mattermost_error(&self) -> Option<&MattermostError> {
+        match self {
+            Self::MattermostApiError(e)
+            | Self::Unauthorized(e)
+            | Self::Forbidden(e)
+            | Self::NotFound(e) => Some(e),
+            Self::RateLimited { error, .. } | Self::WebsocketAuthenticationFailed(error) => {
+                error.as_deref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the error is due to missing, wrong, or expired credentials,
+    /// such that logging in again may help.
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            Self::Unauthorized(_)
+                | Self::MissingAuthToken
+                | Self::CouldNotGetToken(_)
+                | Self::WebsocketAuthenticationFailed(_)
+        )
+    }
+
+    /// Whether the same request may succeed if made again later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } => true,
+            Self::StatusCodeError(status) => *status >= 500,
+            Self::MattermostApiError(e) => e.status_code >= 500,
+            Self::ReqwestError(e) => e.is_timeout() || e.is_connect(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mattermost_error(status_code: i16) -> MattermostError {
+        serde_json::from_value(serde_json::json!({
+            "id": "e", "message": "", "request_id": "r", "status_code": status_code
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn error_helpers() {
+        let unauthorized = ApiError::Unauthorized(mattermost_error(401));
+        assert!(unauthorized.is_auth_error());
+        assert!(!unauthorized.is_retryable());
+        assert_eq!(unauthorized.mattermost_error().unwrap().status_code, 401);
+
+        let forbidden = ApiError::Forbidden(mattermost_error(403));
+        assert!(!forbidden.is_auth_error());
+
+        let rate_limited = ApiError::RateLimited {
+            error: None,
+            retry_after: None,
+            rate_limit: RateLimit::default(),
+        };
+        assert!(rate_limited.is_retryable());
+        assert!(rate_limited.mattermost_error().is_none());
+
+        assert!(ApiError::MattermostApiError(mattermost_error(503)).is_retryable());
+        assert!(ApiError::StatusCodeError(502).is_retryable());
+        assert!(!ApiError::StatusCodeError(400).is_retryable());
+    }
 }
@@ -0,0 +1,539 @@
+//! Utilities for formatting markdown messages for posts.
+
+/// Maximum number of characters the server accepts in a post's message.
+pub const MAX_POST_LENGTH: usize = 16383;
+
+/// Escape a value for use in a markdown table cell.
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Builder for a markdown table.
+///
+/// # Example
+///
+/// ```rust
+/// use mattermost_api::format::MarkdownTable;
+///
+/// let table = MarkdownTable::new(["Service", "Status"])
+///     .row(["api", "up"])
+///     .row(["db", "degraded | read-only"])
+///     .to_markdown();
+/// assert_eq!(
+///     table,
+///     "| Service | Status |\n| --- | --- |\n| api | up |\n| db | degraded \\| read-only |"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownTable {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl MarkdownTable {
+    /// Create a table with the given column headers.
+    pub fn new<I, S>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            headers: headers
+                .into_iter()
+                .map(|h| escape_cell(h.as_ref()))
+                .collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row.
+    ///
+    /// Rows with fewer cells than there are headers are padded with
+    /// empty cells, and cells beyond the number of headers are dropped.
+    pub fn row<I, S>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.push_row(cells);
+        self
+    }
+
+    /// Append a row without consuming the table.
+    pub fn push_row<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut row: Vec<String> = cells
+            .into_iter()
+            .take(self.headers.len())
+            .map(|c| escape_cell(c.as_ref()))
+            .collect();
+        row.resize(self.headers.len(), String::new());
+        self.rows.push(row);
+    }
+
+    /// Render the table as markdown.
+    pub fn to_markdown(&self) -> String {
+        let line = |cells: &[String]| format!("| {} |", cells.join(" | "));
+        let separator = vec!["---".to_owned(); self.headers.len()];
+        let mut lines = vec![line(&self.headers), line(&separator)];
+        lines.extend(self.rows.iter().map(|r| line(r)));
+        lines.join("\n")
+    }
+}
+
+impl std::fmt::Display for MarkdownTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_markdown())
+    }
+}
+
+/// Wrap text in a fenced code block, with optional syntax highlighting.
+///
+/// The fence is made longer than any run of backticks in the
+/// text, so the text can't close the block early.
+///
+/// # Example
+///
+/// ```rust
+/// use mattermost_api::format::code_block;
+///
+/// assert_eq!(code_block("rust", "let x = 1;"), "```rust\nlet x = 1;\n```");
+/// ```
+pub fn code_block(lang: &str, text: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{lang}\n{}\n{fence}", text.trim_end_matches('\n'))
+}
+
+fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// The fence marker if the line opens or closes a fenced code block.
+fn fence_marker(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let c = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(c).len();
+    (len >= 3).then(|| &trimmed[..len])
+}
+
+fn is_table_line(line: &str) -> bool {
+    line.trim_start().starts_with('|')
+}
+
+fn is_table_separator(line: &str) -> bool {
+    is_table_line(line)
+        && line.contains('-')
+        && line
+            .trim()
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+/// Split text into pieces of at most `max_len` characters, breaking on whitespace when possible.
+fn split_long_line(line: &str, max_len: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest: Vec<char> = line.chars().collect();
+    while rest.len() > max_len {
+        let cut = rest[..=max_len]
+            .iter()
+            .rposition(|c| c.is_whitespace())
+            .filter(|&i| i > 0)
+            .unwrap_or(max_len);
+        pieces.push(rest[..cut].iter().collect());
+        let skip = if cut < rest.len() && rest[cut].is_whitespace() {
+            1
+        } else {
+            0
+        };
+        rest.drain(..cut + skip);
+    }
+    pieces.push(rest.into_iter().collect());
+    pieces
+}
+
+/// Accumulates lines into chunks, closing and reopening code fences
+/// and repeating table headers across chunk boundaries.
+struct Chunker {
+    max_len: usize,
+    chunks: Vec<String>,
+    current: Vec<String>,
+    current_len: usize,
+    fence: Option<(String, String)>,
+    table_header: Vec<String>,
+}
+
+impl Chunker {
+    fn new(max_len: usize) -> Self {
+        Self {
+            max_len,
+            chunks: Vec::new(),
+            current: Vec::new(),
+            current_len: 0,
+            fence: None,
+            table_header: Vec::new(),
+        }
+    }
+
+    /// Length of the chunk if `line` were appended to it.
+    fn len_with(&self, line: &str) -> usize {
+        let newline = usize::from(!self.current.is_empty());
+        self.current_len + newline + char_len(line)
+    }
+
+    fn push_line(&mut self, line: String) {
+        self.current_len = self.len_with(&line);
+        self.current.push(line);
+    }
+
+    fn pop_line(&mut self) -> Option<String> {
+        let line = self.current.pop()?;
+        self.current_len -= char_len(&line) + usize::from(!self.current.is_empty());
+        Some(line)
+    }
+
+    /// Lines that must start the next chunk to keep the current block intact.
+    fn continuation(&self) -> Vec<String> {
+        match &self.fence {
+            Some((opening, _)) => vec![opening.clone()],
+            None => self.table_header.clone(),
+        }
+    }
+
+    /// Characters needed to close the current block at the end of a chunk.
+    fn closing_len(&self) -> usize {
+        self.fence
+            .as_ref()
+            .map_or(0, |(_, marker)| 1 + char_len(marker))
+    }
+
+    fn flush(&mut self) {
+        if self.current.is_empty() {
+            return;
+        }
+        let continuation = self.continuation();
+        if let Some((_, marker)) = &self.fence {
+            let marker = marker.clone();
+            self.push_line(marker);
+        }
+        self.chunks.push(self.current.join("\n"));
+        self.current.clear();
+        self.current_len = 0;
+        let overhead: usize = continuation.iter().map(|l| char_len(l) + 1).sum();
+        if overhead + self.closing_len() < self.max_len {
+            for line in continuation {
+                self.push_line(line);
+            }
+        }
+    }
+
+    /// If the current chunk holds nothing but continuation lines.
+    fn only_continuation(&self) -> bool {
+        self.current == self.continuation()
+    }
+
+    fn add_line(&mut self, line: &str) {
+        let fence_change = fence_marker(line).filter(|marker| match &self.fence {
+            Some((_, open)) => marker.starts_with(open.as_str()) && line.trim() == *marker,
+            None => true,
+        });
+        if self.fence.is_none() && !is_table_line(line) {
+            self.table_header.clear();
+        }
+
+        // a closing fence only needs to fit where the reserved closing space was
+        let reserve = if fence_change.is_some() && self.fence.is_some() {
+            0
+        } else {
+            self.closing_len()
+        };
+        // a header row left at the end of a chunk moves to the next one
+        // with its separator, rather than being repeated there
+        let header_ends_chunk = self.fence.is_none()
+            && is_table_separator(line)
+            && self.table_header.len() == 1
+            && self.current.len() > 1
+            && self.current.last() == self.table_header.first();
+        if header_ends_chunk && self.len_with(line) > self.max_len {
+            let header = self.pop_line().unwrap_or_default();
+            self.table_header.clear();
+            self.flush();
+            self.push_line(header.clone());
+            self.table_header = vec![header];
+        }
+        if self.len_with(line) + reserve > self.max_len && !self.only_continuation() {
+            self.flush();
+        }
+        if self.len_with(line) + reserve > self.max_len {
+            let available = self
+                .max_len
+                .saturating_sub(self.current_len + usize::from(!self.current.is_empty()) + reserve)
+                .max(1);
+            for piece in split_long_line(line, available) {
+                if self.len_with(&piece) + reserve > self.max_len && !self.only_continuation() {
+                    self.flush();
+                }
+                self.push_line(piece);
+            }
+        } else {
+            self.push_line(line.to_owned());
+        }
+
+        if self.fence.is_none() && is_table_line(line) {
+            if self.table_header.len() == 1 && is_table_separator(line) {
+                self.table_header.push(line.to_owned());
+            } else if self.table_header.len() < 2 {
+                self.table_header = vec![line.to_owned()];
+            }
+        }
+        if let Some(marker) = fence_change {
+            self.fence = match self.fence {
+                Some(_) => None,
+                None => Some((line.to_owned(), marker.to_owned())),
+            };
+        }
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if !self.current.is_empty() && !self.only_continuation() {
+            self.chunks.push(self.current.join("\n"));
+        }
+        self.chunks
+    }
+}
+
+/// Split paragraphs (runs of lines separated by blank lines outside of code blocks).
+fn paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut fence: Option<&str> = None;
+    for line in text.lines() {
+        match (fence, fence_marker(line)) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if marker.starts_with(open) && line.trim() == marker => {
+                fence = None
+            }
+            _ => {}
+        }
+        if fence.is_none() && line.trim().is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(current.join("\n"));
+                current.clear();
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current.join("\n"));
+    }
+    paragraphs
+}
+
+/// Split a message into pieces of at most `max_len` characters each.
+///
+/// Pieces are split between paragraphs where possible, then between
+/// lines. A code block that has to be split is closed at the end of
+/// one piece and reopened, with the same language, at the start of
+/// the next; a table that has to be split repeats its header rows.
+/// Only single lines longer than `max_len` are split mid-line, on
+/// whitespace where possible.
+///
+/// Use [`MAX_POST_LENGTH`] as `max_len` to split a message into posts.
+///
+/// # Example
+///
+/// ```rust
+/// use mattermost_api::format::split_message;
+///
+/// let pieces = split_message("first paragraph\n\nsecond paragraph", 20);
+/// assert_eq!(pieces, vec!["first paragraph", "second paragraph"]);
+/// ```
+pub fn split_message(text: &str, max_len: usize) -> Vec<String> {
+    let max_len = max_len.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in paragraphs(text) {
+        let joined_len = if current.is_empty() {
+            char_len(&paragraph)
+        } else {
+            char_len(&current) + 2 + char_len(&paragraph)
+        };
+        if joined_len <= max_len {
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(&paragraph);
+            continue;
+        }
+        if !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if char_len(&paragraph) <= max_len {
+            current = paragraph;
+        } else {
+            let mut chunker = Chunker::new(max_len);
+            for line in paragraph.lines() {
+                chunker.add_line(line);
+            }
+            let mut pieces = chunker.finish();
+            current = pieces.pop().unwrap_or_default();
+            chunks.extend(pieces);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_within(pieces: &[String], max_len: usize) {
+        for piece in pieces {
+            assert!(
+                char_len(piece) <= max_len,
+                "piece of {} chars exceeds {max_len}: {piece:?}",
+                char_len(piece)
+            );
+        }
+    }
+
+    #[test]
+    fn table_escapes_and_pads_cells() {
+        let table =
+            MarkdownTable::new(["a|b", "c"])
+                .row(["1"])
+                .row(["2", "line\nbreak", "dropped"]);
+        assert_eq!(
+            table.to_string(),
+            "| a\\|b | c |\n| --- | --- |\n| 1 |  |\n| 2 | line break |"
+        );
+    }
+
+    #[test]
+    fn code_block_fence_outgrows_backticks_in_text() {
+        assert_eq!(code_block("", "plain\n"), "```\nplain\n```");
+        assert_eq!(
+            code_block("md", "```rust\nfn main() {}\n```"),
+            "````md\n```rust\nfn main() {}\n```\n````"
+        );
+    }
+
+    #[test]
+    fn short_text_is_one_piece() {
+        assert_eq!(split_message("hello", 100), vec!["hello"]);
+        assert_eq!(split_message("", 100), Vec::<String>::new());
+    }
+
+    #[test]
+    fn splits_on_paragraphs_first() {
+        let text = "aaaa aaaa\n\nbbbb\nbbbb\n\ncccc";
+        assert_eq!(
+            split_message(text, 15),
+            vec!["aaaa aaaa", "bbbb\nbbbb\n\ncccc"]
+        );
+    }
+
+    #[test]
+    fn splits_long_paragraph_between_lines() {
+        let pieces = split_message("one\ntwo\nthree\nfour", 9);
+        assert_eq!(pieces, vec!["one\ntwo", "three", "four"]);
+    }
+
+    #[test]
+    fn splits_long_lines_on_whitespace() {
+        let pieces = split_message("the quick brown fox jumps", 10);
+        assert_eq!(pieces, vec!["the quick", "brown fox", "jumps"]);
+        assert_eq!(split_message("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn split_counts_characters_not_bytes() {
+        let pieces = split_message("ééééé ééééé", 5);
+        assert_eq!(pieces, vec!["ééééé", "ééééé"]);
+    }
+
+    #[test]
+    fn code_fence_is_closed_and_reopened() {
+        let text = "intro\n```rust\nline1\nline2\nline3\n```\noutro";
+        let pieces = split_message(text, 24);
+        assert_within(&pieces, 24);
+        assert_eq!(
+            pieces,
+            vec![
+                "intro\n```rust\nline1\n```",
+                "```rust\nline2\nline3\n```",
+                "outro"
+            ]
+        );
+    }
+
+    #[test]
+    fn blank_lines_inside_code_fence_are_not_paragraph_breaks() {
+        let text = "```\na\n\nb\n```";
+        assert_eq!(paragraphs(text), vec![text]);
+        let pieces = split_message(text, 9);
+        assert_within(&pieces, 9);
+        assert_eq!(pieces, vec!["```\na\n```", "```\n\n```", "```\nb\n```"]);
+    }
+
+    #[test]
+    fn every_piece_has_balanced_fences() {
+        let body: Vec<String> = (0..50).map(|i| format!("let x{i} = {i};")).collect();
+        let text = format!("Result:\n{}", code_block("rust", &body.join("\n")));
+        let pieces = split_message(&text, 100);
+        assert!(pieces.len() > 1);
+        assert_within(&pieces, 100);
+        for piece in &pieces {
+            let fences = piece.lines().filter(|l| l.starts_with("```")).count();
+            assert_eq!(fences % 2, 0, "unbalanced fences in {piece:?}");
+        }
+        let rejoined: Vec<&str> = pieces
+            .iter()
+            .flat_map(|p| p.lines())
+            .filter(|l| l.starts_with("let"))
+            .collect();
+        assert_eq!(rejoined, body);
+    }
+
+    #[test]
+    fn table_header_is_repeated() {
+        let mut table = MarkdownTable::new(["n", "square"]);
+        for i in 0..6 {
+            table.push_row([i.to_string(), (i * i).to_string()]);
+        }
+        let pieces = split_message(&table.to_markdown(), 60);
+        assert_within(&pieces, 60);
+        assert_eq!(
+            pieces,
+            vec![
+                "| n | square |\n| --- | --- |\n| 0 | 0 |\n| 1 | 1 |\n| 2 | 4 |",
+                "| n | square |\n| --- | --- |\n| 3 | 9 |\n| 4 | 16 |\n| 5 | 25 |",
+            ]
+        );
+    }
+
+    #[test]
+    fn table_starting_at_a_split_is_not_duplicated() {
+        let text = "some text\n| a |\n| --- |\n| 1 |";
+        let pieces = split_message(text, 20);
+        assert_eq!(pieces, vec!["some text", "| a |\n| --- |\n| 1 |"]);
+    }
+
+    #[test]
+    fn table_ends_with_following_text() {
+        let text = "| a |\n| --- |\n| 1 |\nafter the table\nmore";
+        let pieces = split_message(text, 20);
+        assert_within(&pieces, 20);
+        assert_eq!(
+            pieces,
+            vec!["| a |\n| --- |\n| 1 |", "after the table\nmore"]
+        );
+    }
+}
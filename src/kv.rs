@@ -0,0 +1,274 @@
+//! Key-value storage in a user's preferences.
+//!
+//! Small bots that need a little persistent state, such as the
+//! timestamp of the last post they processed, can keep it in their
+//! own preferences on the instance instead of a separate database.
+
+use crate::{models::Preference, prelude::*};
+
+/// Maximum number of bytes in a preference category.
+pub const MAX_CATEGORY_LENGTH: usize = 32;
+/// Maximum number of bytes in a preference name.
+pub const MAX_NAME_LENGTH: usize = 32;
+/// Maximum number of characters in a preference value.
+pub const MAX_VALUE_LENGTH: usize = 2000;
+
+fn check_length(field: &'static str, actual: usize, limit: usize) -> Result<(), ApiError> {
+    if actual > limit {
+        return Err(ApiError::PreferenceTooLong {
+            field,
+            limit,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Key-value store kept in a user's preferences, under a single category.
+///
+/// Keys are limited to [`MAX_NAME_LENGTH`] bytes and values to
+/// [`MAX_VALUE_LENGTH`] characters, as the server counts them, and
+/// longer ones are rejected with `ApiError::PreferenceTooLong`
+/// before any request is made.
+///
+/// Writes are last-writer-wins: the server has no compare-and-swap for
+/// preferences, so if several processes share a store they must not
+/// write the same key without coordinating. Every call makes a request,
+/// so a value written by another process is seen on the next `get`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mattermost_api::{kv::BotKvStore, prelude::*};
+/// # async fn run() {
+/// let auth = AuthenticationData::from_access_token("token");
+/// let api = Mattermost::new("https://your-mattermost-instance.com", auth).unwrap();
+/// let store = BotKvStore::new(&api, "bot-user-id", "my_bot_state").unwrap();
+/// store.set("last_seen", "1700000000000").await.unwrap();
+/// let last_seen = store.get("last_seen").await.unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BotKvStore<'a> {
+    api: &'a Mattermost,
+    user_id: String,
+    category: String,
+}
+
+impl<'a> BotKvStore<'a> {
+    /// Create a store in the preferences of the user, under the category.
+    ///
+    /// The user is typically the bot itself; storing in another
+    /// user's preferences requires the "edit_other_users" permission.
+    ///
+    /// The category must not be empty, and is limited to
+    /// [`MAX_CATEGORY_LENGTH`] bytes.
+    pub fn new(
+        api: &'a Mattermost,
+        user_id: impl Into<String>,
+        category: impl Into<String>,
+    ) -> Result<Self, ApiError> {
+        let category = category.into();
+        if category.is_empty() {
+            return Err(ApiError::EmptyPreferenceCategory);
+        }
+        check_length("category", category.len(), MAX_CATEGORY_LENGTH)?;
+        Ok(Self {
+            api,
+            user_id: user_id.into(),
+            category,
+        })
+    }
+
+    fn preference(&self, key: &str, value: &str) -> Preference {
+        Preference {
+            user_id: self.user_id.clone(),
+            category: self.category.clone(),
+            name: key.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    /// Get the value for a key, or `None` if it isn't set.
+    pub async fn get(&self, key: &str) -> Result<Option<String>, ApiError> {
+        check_length("name", key.len(), MAX_NAME_LENGTH)?;
+        match self
+            .api
+            .get_user_preference(&self.user_id, &self.category, key)
+            .await
+        {
+            Ok(preference) => Ok(Some(preference.value)),
+            Err(ApiError::NotFound(_)) => Ok(None),
+            Err(ApiError::StatusCodeError(404)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Set the value for a key, replacing any existing value.
+    pub async fn set(&self, key: &str, value: &str) -> Result<(), ApiError> {
+        check_length("name", key.len(), MAX_NAME_LENGTH)?;
+        check_length("value", value.chars().count(), MAX_VALUE_LENGTH)?;
+        self.api
+            .save_user_preferences(&self.user_id, &[self.preference(key, value)])
+            .await?;
+        Ok(())
+    }
+
+    /// Remove a key. Removing a key that isn't set is not an error.
+    pub async fn delete(&self, key: &str) -> Result<(), ApiError> {
+        check_length("name", key.len(), MAX_NAME_LENGTH)?;
+        self.api
+            .delete_user_preferences(&self.user_id, &[self.preference(key, "")])
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{mock, Matcher};
+
+    fn api() -> Mattermost {
+        Mattermost::new(
+            mockito::server_url(),
+            AuthenticationData::from_access_token("x"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn lengths_are_checked() {
+        let api = api();
+        assert!(matches!(
+            BotKvStore::new(&api, "u", "c".repeat(33)),
+            Err(ApiError::PreferenceTooLong {
+                field: "category",
+                limit: 32,
+                actual: 33
+            })
+        ));
+        assert!(matches!(
+            BotKvStore::new(&api, "u", "é".repeat(32)),
+            Err(ApiError::PreferenceTooLong {
+                field: "category",
+                limit: 32,
+                actual: 64
+            })
+        ));
+        assert!(BotKvStore::new(&api, "u", "é".repeat(16)).is_ok());
+        assert!(matches!(
+            BotKvStore::new(&api, "u", ""),
+            Err(ApiError::EmptyPreferenceCategory)
+        ));
+    }
+
+    #[tokio::test]
+    async fn oversized_values_are_rejected_without_a_request() {
+        let api = api();
+        let store = BotKvStore::new(&api, "u", "state").unwrap();
+        let err = store.set("key", &"v".repeat(2001)).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ApiError::PreferenceTooLong {
+                field: "value",
+                limit: 2000,
+                actual: 2001
+            }
+        ));
+        let err = store.get(&"k".repeat(33)).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ApiError::PreferenceTooLong { field: "name", .. }
+        ));
+        let err = store.get(&"é".repeat(32)).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ApiError::PreferenceTooLong {
+                field: "name",
+                limit: 32,
+                actual: 64
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn values_are_counted_in_characters() {
+        let set = mock("PUT", "/api/v4/users/u/preferences")
+            .match_body(Matcher::Json(serde_json::json!([
+                {"user_id": "u", "category": "state", "name": "wide", "value": "é".repeat(2000)}
+            ])))
+            .with_body(r#"{"status": "OK"}"#)
+            .create();
+
+        let api = api();
+        let store = BotKvStore::new(&api, "u", "state").unwrap();
+        store.set("wide", &"é".repeat(2000)).await.unwrap();
+        set.assert();
+    }
+
+    #[tokio::test]
+    async fn get_set_and_delete() {
+        let found = mock("GET", "/api/v4/users/u/preferences/state/name/last_seen")
+            .with_body(
+                r#"{"user_id": "u", "category": "state", "name": "last_seen", "value": "42"}"#,
+            )
+            .create();
+        let missing = mock("GET", "/api/v4/users/u/preferences/state/name/other")
+            .with_status(404)
+            .with_body(
+                r#"{"id": "app.preference.get.app_error", "message": "",
+                    "request_id": "r", "status_code": 404}"#,
+            )
+            .create();
+        let set = mock("PUT", "/api/v4/users/u/preferences")
+            .match_body(Matcher::Json(serde_json::json!([
+                {"user_id": "u", "category": "state", "name": "last_seen", "value": "43"}
+            ])))
+            .with_body(r#"{"status": "OK"}"#)
+            .create();
+        let delete = mock("POST", "/api/v4/users/u/preferences/delete")
+            .match_body(Matcher::Json(serde_json::json!([
+                {"user_id": "u", "category": "state", "name": "last_seen", "value": ""}
+            ])))
+            .with_body(r#"{"status": "OK"}"#)
+            .create();
+
+        let api = api();
+        let store = BotKvStore::new(&api, "u", "state").unwrap();
+        assert_eq!(store.get("last_seen").await.unwrap().as_deref(), Some("42"));
+        assert_eq!(store.get("other").await.unwrap(), None);
+        store.set("last_seen", "43").await.unwrap();
+        store.delete("last_seen").await.unwrap();
+
+        found.assert();
+        missing.assert();
+        set.assert();
+        delete.assert();
+    }
+
+    #[tokio::test]
+    async fn keys_are_encoded_in_paths() {
+        let key = "a/b?c#d e%";
+        let found = mock(
+            "GET",
+            "/api/v4/users/u/preferences/state%20x/name/a%2Fb%3Fc%23d%20e%25",
+        )
+        .with_body(r#"{"user_id": "u", "category": "state x", "name": "a/b?c#d e%", "value": "1"}"#)
+        .create();
+        let set = mock("PUT", "/api/v4/users/u/preferences")
+            .match_body(Matcher::Json(serde_json::json!([
+                {"user_id": "u", "category": "state x", "name": key, "value": "1"}
+            ])))
+            .with_body(r#"{"status": "OK"}"#)
+            .create();
+
+        let api = api();
+        let store = BotKvStore::new(&api, "u", "state x").unwrap();
+        store.set(key, "1").await.unwrap();
+        assert_eq!(store.get(key).await.unwrap().as_deref(), Some("1"));
+
+        found.assert();
+        set.assert();
+    }
+}
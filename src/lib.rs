@@ -20,6 +20,21 @@
 //! # }
 //! ```
 //!
+//! # Features
+//!
+//! The TLS backend used by both the HTTP client and the websocket
+//! connection is selected with cargo features:
+//!
+//! - `native-tls` (default): the platform's TLS library, e.g. OpenSSL.
+//! - `rustls-tls`: rustls with the webpki root certificates; build with
+//!   `--no-default-features --features rustls-tls` to drop OpenSSL.
+//! - `rustls-native-certs`: rustls with the platform's root certificates.
+//!
+//! `ws-keep-alive` (default) sends periodic pings on the websocket
+//! connection, and `socks` lets the HTTP client use SOCKS5 proxies.
+//! `strict-models` makes responses with fields that the typed models
+//! would drop an `ApiError::UnknownFields` error, to find them in tests.
+//!
 //! [their docs]: https://api.mattermost.com
 //! [`AuthenticationData`]: struct.AuthenticationData.html
 //! [`Mattermost::new`]: struct.Mattermost.html
@@ -30,8 +45,17 @@
 
 pub mod client;
 pub mod errors;
+pub mod format;
+pub mod kv;
+pub mod mentions;
 pub mod models;
+pub mod observer;
 pub mod prelude;
+pub mod presence;
+mod proxy;
+pub mod retry;
 pub mod socket;
+pub mod stats;
+mod user_cache;
 /// Re-exported since websocket events have untyped data for now
 pub use serde_json::Value;
@@ -0,0 +1,591 @@
+//! Utilities for building and parsing mentions in messages.
+//!
+//! Mattermost recognizes `@username` for users, `~channel-name` for
+//! channels, and the special `@here`, `@channel`, and `@all` mentions.
+//! A mention only starts at the beginning of the message or after a
+//! character that can't be part of a name, so `you@example.com` does
+//! not mention anyone, and mentions inside code are ignored.
+
+use crate::{models, prelude::*};
+use std::collections::HashMap;
+
+/// Special mentions that notify groups of channel members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpecialMention {
+    /// `@here`, notifying online channel members.
+    Here,
+    /// `@channel`, notifying all channel members.
+    Channel,
+    /// `@all`, notifying all channel members.
+    All,
+}
+
+impl SpecialMention {
+    /// The name of the mention, without the leading `@`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Here => "here",
+            Self::Channel => "channel",
+            Self::All => "all",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "here" => Some(Self::Here),
+            "channel" => Some(Self::Channel),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+
+/// A mention found in a message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Mention {
+    /// `@username`, holding the lowercased username.
+    User(String),
+    /// `~channel-name`, holding the lowercased channel name.
+    Channel(String),
+    /// One of the special group mentions.
+    Special(SpecialMention),
+}
+
+/// A mention with the id of what it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedMention {
+    #[allow(missing_docs)]
+    User { username: String, user_id: String },
+    #[allow(missing_docs)]
+    Channel { name: String, channel_id: String },
+    /// Special mentions don't refer to a single user or channel.
+    Special(SpecialMention),
+}
+
+/// If the character can be part of a username or channel name.
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')
+}
+
+/// Zero-width joiner, inserted by `MentionBuilder` to keep text
+/// from forming a mention or running on into the one before it.
+const MENTION_BREAK: char = '\u{200D}';
+
+/// Remove inline code spans and fenced code blocks from a message,
+/// since the server doesn't treat mentions inside them as mentions.
+fn strip_code(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut in_fence = false;
+    for line in message.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            out.push('\n');
+            continue;
+        }
+        if in_fence {
+            out.push('\n');
+            continue;
+        }
+        let mut in_span = false;
+        for c in line.chars() {
+            if c == '`' {
+                in_span = !in_span;
+                out.push(' ');
+            } else if !in_span {
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+/// Find the mentions in a message, in order of first appearance and without duplicates.
+///
+/// Trailing periods are not treated as part of a name, so that a
+/// mention at the end of a sentence is still recognized.
+///
+/// # Example
+///
+/// ```rust
+/// use mattermost_api::mentions::{extract_mentions, Mention, SpecialMention};
+///
+/// let mentions = extract_mentions("@here, ping @alice about ~town-square.");
+/// assert_eq!(
+///     mentions,
+///     vec![
+///         Mention::Special(SpecialMention::Here),
+///         Mention::User("alice".to_owned()),
+///         Mention::Channel("town-square".to_owned()),
+///     ]
+/// );
+/// ```
+pub fn extract_mentions(message: &str) -> Vec<Mention> {
+    let text = strip_code(message);
+    let mut mentions = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let at_boundary = prev.is_none_or(|p| !is_name_char(p) && p != '@' && p != '~');
+        prev = Some(c);
+        if !(at_boundary && (c == '@' || c == '~')) {
+            continue;
+        }
+        let start = i + c.len_utf8();
+        let mut end = start;
+        while let Some(&(j, n)) = chars.peek() {
+            if !is_name_char(n) {
+                break;
+            }
+            end = j + n.len_utf8();
+            prev = Some(n);
+            chars.next();
+        }
+        let name = text[start..end].trim_end_matches('.').to_lowercase();
+        if !name.starts_with(|n: char| n.is_ascii_alphabetic()) {
+            continue;
+        }
+
+        let mention = match (c, SpecialMention::from_name(&name)) {
+            ('@', Some(special)) => Mention::Special(special),
+            ('@', None) => Mention::User(name),
+            _ => Mention::Channel(name),
+        };
+        if !mentions.contains(&mention) {
+            mentions.push(mention);
+        }
+    }
+
+    mentions
+}
+
+/// Look up the ids of the users and channels that mentions refer to.
+///
+/// Users are looked up in a single request, and channels are looked
+/// up by name within the team. Mentions of users or channels that
+/// don't exist are left out of the result.
+pub async fn resolve_mentions(
+    api: &Mattermost,
+    team_id: &str,
+    mentions: &[Mention],
+) -> Result<Vec<ResolvedMention>, ApiError> {
+    let usernames: Vec<&str> = mentions
+        .iter()
+        .filter_map(|m| match m {
+            Mention::User(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let users: HashMap<String, models::UserInformation> = if usernames.is_empty() {
+        HashMap::new()
+    } else {
+        api.get_users_by_usernames(&usernames)
+            .await?
+            .into_iter()
+            .map(|user| (user.username.clone(), user))
+            .collect()
+    };
+
+    let mut resolved = Vec::with_capacity(mentions.len());
+    for mention in mentions {
+        match mention {
+            Mention::User(username) => {
+                if let Some(user) = users.get(username) {
+                    resolved.push(ResolvedMention::User {
+                        username: username.clone(),
+                        user_id: user.id.clone(),
+                    });
+                }
+            }
+            Mention::Channel(name) => match api.get_channel_by_name(team_id, name).await {
+                Ok(channel) => resolved.push(ResolvedMention::Channel {
+                    name: name.clone(),
+                    channel_id: channel.id,
+                }),
+                Err(ApiError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            },
+            Mention::Special(special) => resolved.push(ResolvedMention::Special(*special)),
+        }
+    }
+    Ok(resolved)
+}
+
+/// Escape characters that Mattermost would otherwise render as markdown.
+pub fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '~' | '[' | ']' | '#' | '>' | '|' | '<'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Text(String),
+    Mention(Mention),
+}
+
+/// Builder for messages containing mentions.
+///
+/// Text added with `text` is escaped so that it can't be rendered
+/// as markdown. An `@` or `~` in it that could start a mention, and
+/// text that would otherwise run on into the mention before it, are
+/// broken up with a zero-width joiner so that they don't change which
+/// users and channels the message mentions.
+///
+/// # Example
+///
+/// ```rust
+/// use mattermost_api::mentions::MentionBuilder;
+///
+/// let message = MentionBuilder::new()
+///     .user("alice")
+///     .text(" the *build* is broken, see ")
+///     .channel("ci-failures")
+///     .build();
+/// assert_eq!(message, r"@alice the \*build\* is broken, see ~ci-failures");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MentionBuilder {
+    segments: Vec<Segment>,
+}
+
+impl MentionBuilder {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append text, escaping any markdown and mentions in it.
+    pub fn text(mut self, text: impl AsRef<str>) -> Self {
+        self.segments
+            .push(Segment::Text(escape_markdown(text.as_ref())));
+        self
+    }
+
+    /// Append a mention of a user by their username, with or without the leading `@`.
+    pub fn user(mut self, username: impl AsRef<str>) -> Self {
+        let name = username.as_ref().trim_start_matches('@').to_lowercase();
+        self.segments.push(Segment::Mention(Mention::User(name)));
+        self
+    }
+
+    /// Append a mention of a channel by its name, with or without the leading `~`.
+    pub fn channel(mut self, name: impl AsRef<str>) -> Self {
+        let name = name.as_ref().trim_start_matches('~').to_lowercase();
+        self.segments.push(Segment::Mention(Mention::Channel(name)));
+        self
+    }
+
+    /// Append one of the special group mentions.
+    pub fn special(mut self, mention: SpecialMention) -> Self {
+        self.segments
+            .push(Segment::Mention(Mention::Special(mention)));
+        self
+    }
+
+    /// The mentions added to the builder.
+    pub fn mentions(&self) -> Vec<&Mention> {
+        self.segments
+            .iter()
+            .filter_map(|s| match s {
+                Segment::Mention(m) => Some(m),
+                Segment::Text(_) => None,
+            })
+            .collect()
+    }
+
+    /// Build the message.
+    pub fn build(&self) -> String {
+        let mut out = String::new();
+        let mut after_mention = false;
+        for segment in &self.segments {
+            let (sigil, name) = match segment {
+                Segment::Text(text) => {
+                    for c in text.chars() {
+                        if after_mention && is_name_char(c) {
+                            out.push(MENTION_BREAK);
+                        }
+                        after_mention = false;
+                        let at_boundary = out.chars().next_back().is_none_or(|p| !is_name_char(p));
+                        out.push(c);
+                        if at_boundary && (c == '@' || c == '~') {
+                            out.push(MENTION_BREAK);
+                        }
+                    }
+                    continue;
+                }
+                Segment::Mention(Mention::User(name)) => ('@', name.as_str()),
+                Segment::Mention(Mention::Channel(name)) => ('~', name.as_str()),
+                Segment::Mention(Mention::Special(special)) => ('@', special.name()),
+            };
+            out.push(sigil);
+            out.push_str(name);
+            after_mention = true;
+        }
+        out
+    }
+
+    /// Build the message after checking that every mentioned
+    /// username is valid and belongs to a user on the instance.
+    ///
+    /// Returns `ApiError::UnknownUsernames` listing any that don't.
+    pub async fn build_validated(&self, api: &Mattermost) -> Result<String, ApiError> {
+        let usernames: Vec<&str> = self
+            .mentions()
+            .into_iter()
+            .filter_map(|m| match m {
+                Mention::User(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        let (mut unknown, valid): (Vec<&str>, Vec<&str>) =
+            usernames.into_iter().partition(|name| {
+                !name.starts_with(|c: char| c.is_ascii_alphabetic())
+                    || !name.chars().all(is_name_char)
+            });
+        if !valid.is_empty() {
+            let found: Vec<String> = api
+                .get_users_by_usernames(&valid)
+                .await?
+                .into_iter()
+                .map(|user| user.username)
+                .collect();
+            unknown.extend(
+                valid
+                    .into_iter()
+                    .filter(|name| !found.iter().any(|f| f == name)),
+            );
+        }
+        if !unknown.is_empty() {
+            return Err(ApiError::UnknownUsernames(
+                unknown.into_iter().map(String::from).collect(),
+            ));
+        }
+        Ok(self.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(name: &str) -> Mention {
+        Mention::User(name.to_owned())
+    }
+
+    fn channel(name: &str) -> Mention {
+        Mention::Channel(name.to_owned())
+    }
+
+    #[test]
+    fn extract_mentions_table() {
+        let cases: Vec<(&str, Vec<Mention>)> = vec![
+            ("", vec![]),
+            ("no mentions here", vec![]),
+            ("@alice", vec![user("alice")]),
+            ("hi @alice!", vec![user("alice")]),
+            ("hi @alice.", vec![user("alice")]),
+            ("(@alice)", vec![user("alice")]),
+            ("@alice, @bob: hi", vec![user("alice"), user("bob")]),
+            ("@Alice and @alice", vec![user("alice")]),
+            ("@john.doe said", vec![user("john.doe")]),
+            ("@first_last-name", vec![user("first_last-name")]),
+            ("you@example.com", vec![]),
+            ("@@alice", vec![]),
+            ("@123", vec![]),
+            ("@", vec![]),
+            ("@here", vec![Mention::Special(SpecialMention::Here)]),
+            ("@channel!", vec![Mention::Special(SpecialMention::Channel)]),
+            ("@all", vec![Mention::Special(SpecialMention::All)]),
+            ("@heretic", vec![user("heretic")]),
+            ("see ~town-square", vec![channel("town-square")]),
+            ("see ~town-square.", vec![channel("town-square")]),
+            (
+                "~off-topic, ~town-square",
+                vec![channel("off-topic"), channel("town-square")],
+            ),
+            ("approx~5", vec![]),
+            ("~~strikethrough~~", vec![]),
+            ("`@alice` @bob", vec![user("bob")]),
+            ("```\n@alice\n```\n@bob", vec![user("bob")]),
+            ("héllo @ünicode", vec![]),
+            ("@alice\n@bob", vec![user("alice"), user("bob")]),
+        ];
+
+        for (message, expected) in cases {
+            assert_eq!(extract_mentions(message), expected, "message: {message:?}");
+        }
+    }
+
+    #[test]
+    fn builder_escapes_text_but_not_mentions() {
+        let message = MentionBuilder::new()
+            .special(SpecialMention::Here)
+            .text(" deploy of `v1.2` is done, thanks ")
+            .user("@Alice")
+            .text(" | see ")
+            .channel("~releases")
+            .build();
+        assert_eq!(
+            message,
+            r"@here deploy of \`v1.2\` is done, thanks @alice \| see ~releases"
+        );
+    }
+
+    #[test]
+    fn built_messages_round_trip() {
+        let builder = MentionBuilder::new()
+            .user("alice")
+            .text(" and ")
+            .user("bob.smith")
+            .text(" in ")
+            .channel("town-square");
+        let mentions: Vec<Mention> = builder.mentions().into_iter().cloned().collect();
+        assert_eq!(extract_mentions(&builder.build()), mentions);
+    }
+
+    #[test]
+    fn builder_text_cannot_form_mentions() {
+        let message = MentionBuilder::new()
+            .text("cc @all and ~town-square, mail me@example.com")
+            .build();
+        assert_eq!(
+            message,
+            "cc @\u{200D}all and \\~\u{200D}town-square, mail me@example.com"
+        );
+        assert_eq!(extract_mentions(&message), vec![]);
+
+        let message = MentionBuilder::new().text("@@here").build();
+        assert_eq!(extract_mentions(&message), vec![]);
+    }
+
+    #[test]
+    fn builder_text_does_not_run_on_into_mentions() {
+        let message = MentionBuilder::new()
+            .user("alice")
+            .text("-team and ")
+            .channel("releases")
+            .text(".old, ")
+            .special(SpecialMention::Here)
+            .text("abouts")
+            .build();
+        assert_eq!(
+            message,
+            "@alice\u{200D}-team and ~releases\u{200D}.old, @here\u{200D}abouts"
+        );
+        assert_eq!(
+            extract_mentions(&message),
+            vec![
+                user("alice"),
+                channel("releases"),
+                Mention::Special(SpecialMention::Here)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn build_validated_reports_unknown_usernames() {
+        let partial = mockito::mock("POST", "/api/v4/users/usernames")
+            .match_body(r#"["carol","dave"]"#)
+            .with_body(
+                r#"[{"id": "carol-id", "username": "carol", "create_at": 0,
+                     "update_at": 0, "delete_at": 0}]"#,
+            )
+            .create();
+        let all = mockito::mock("POST", "/api/v4/users/usernames")
+            .match_body(r#"["carol"]"#)
+            .with_body(
+                r#"[{"id": "carol-id", "username": "carol", "create_at": 0,
+                     "update_at": 0, "delete_at": 0}]"#,
+            )
+            .create();
+
+        let api = Mattermost::new(
+            mockito::server_url(),
+            AuthenticationData::from_access_token("x"),
+        )
+        .unwrap();
+        let err = MentionBuilder::new()
+            .user("carol")
+            .text(" ")
+            .user("Dave")
+            .text(" ")
+            .user("9lives")
+            .build_validated(&api)
+            .await
+            .unwrap_err();
+        match err {
+            ApiError::UnknownUsernames(names) => assert_eq!(names, vec!["9lives", "dave"]),
+            e => panic!("unexpected error: {e:?}"),
+        }
+
+        let message = MentionBuilder::new()
+            .text("hi ")
+            .user("carol")
+            .build_validated(&api)
+            .await
+            .unwrap();
+        assert_eq!(message, "hi @carol");
+        partial.assert();
+        all.assert();
+    }
+
+    #[tokio::test]
+    async fn resolve_mentions_uses_bulk_user_lookup() {
+        let users = mockito::mock("POST", "/api/v4/users/usernames")
+            .match_body(r#"["alice","nobody"]"#)
+            .with_body(
+                r#"[{"id": "alice-id", "username": "alice", "create_at": 0,
+                     "update_at": 0, "delete_at": 0}]"#,
+            )
+            .create();
+        let channel = mockito::mock("GET", "/api/v4/teams/team-id/channels/name/town-square")
+            .with_body(
+                r#"{"id": "channel-id", "create_at": 0, "update_at": 0, "delete_at": 0,
+                    "team_id": "team-id", "type": "O", "display_name": "Town Square",
+                    "name": "town-square"}"#,
+            )
+            .create();
+        let missing_channel = mockito::mock("GET", "/api/v4/teams/team-id/channels/name/gone")
+            .with_status(404)
+            .with_body(
+                r#"{"id": "app.channel.get_by_name.missing.app_error", "message": "",
+                    "request_id": "r", "status_code": 404}"#,
+            )
+            .create();
+
+        let api = Mattermost::new(
+            mockito::server_url(),
+            AuthenticationData::from_access_token("x"),
+        )
+        .unwrap();
+        let mentions = extract_mentions("@alice @nobody ~town-square ~gone @all");
+        let resolved = resolve_mentions(&api, "team-id", &mentions).await.unwrap();
+
+        assert_eq!(
+            resolved,
+            vec![
+                ResolvedMention::User {
+                    username: "alice".to_owned(),
+                    user_id: "alice-id".to_owned()
+                },
+                ResolvedMention::Channel {
+                    name: "town-square".to_owned(),
+                    channel_id: "channel-id".to_owned()
+                },
+                ResolvedMention::Special(SpecialMention::All),
+            ]
+        );
+        users.assert();
+        channel.assert();
+        missing_channel.assert();
+    }
+}
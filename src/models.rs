@@ -2,7 +2,183 @@
 
 #![allow(missing_docs)]
 
-use serde::Deserialize;
+use crate::errors::ApiError;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Define an enum of the string values a field can have.
+///
+/// Values that aren't known to this library deserialize into an
+/// `Unknown` variant holding the string rather than failing, since
+/// the server adds new values over time. Such values serialize back
+/// to the same string.
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)+
+            /// A value not known to this library.
+            Unknown(String),
+        }
+
+        impl $name {
+            /// The string value sent to and from the server.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)+
+                    Self::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => Self::$variant,)+
+                    other => Self::Unknown(other.to_owned()),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = std::borrow::Cow::<str>::deserialize(deserializer)?;
+                Ok(Self::from(value.as_ref()))
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// Type of a channel.
+    pub enum ChannelType {
+        Open = "O",
+        Private = "P",
+        Direct = "D",
+        Group = "G",
+    }
+}
+
+string_enum! {
+    /// Who can join a team.
+    pub enum TeamType {
+        /// Anyone on the instance.
+        Open = "O",
+        /// Only invited users.
+        Invite = "I",
+    }
+}
+
+string_enum! {
+    /// Status of a background job.
+    pub enum JobStatus {
+        Pending = "pending",
+        InProgress = "in_progress",
+        Success = "success",
+        Error = "error",
+        CancelRequested = "cancel_requested",
+        Canceled = "canceled",
+        Warning = "warning",
+    }
+}
+
+string_enum! {
+    /// What a permissions scheme applies to.
+    pub enum SchemeScope {
+        Team = "team",
+        Channel = "channel",
+    }
+}
+
+string_enum! {
+    /// Where the members of a group come from.
+    pub enum GroupSource {
+        Ldap = "ldap",
+        Custom = "custom",
+    }
+}
+
+string_enum! {
+    /// HTTP method a slash command's URL is requested with.
+    #[derive(Default)]
+    pub enum CommandMethod {
+        #[default]
+        Post = "P",
+        Get = "G",
+    }
+}
+
+string_enum! {
+    /// Who sees the response to a slash command.
+    #[derive(Default)]
+    pub enum CommandResponseType {
+        /// Only the user who ran the command.
+        #[default]
+        Ephemeral = "ephemeral",
+        /// Everyone in the channel.
+        InChannel = "in_channel",
+    }
+}
+
+string_enum! {
+    /// Which messages in a channel a user is notified of.
+    pub enum NotifyLevel {
+        /// Use the user's account-wide setting.
+        Default = "default",
+        All = "all",
+        Mention = "mention",
+        None = "none",
+    }
+}
+
+string_enum! {
+    /// Whether "@channel", "@here", and "@all" notify a user in a channel.
+    pub enum IgnoreChannelMentions {
+        /// Use the user's account-wide setting.
+        Default = "default",
+        /// The mentions notify the user.
+        Off = "off",
+        /// The mentions are ignored.
+        On = "on",
+    }
+}
+
+string_enum! {
+    /// What a channel bookmark points to.
+    #[derive(Default)]
+    pub enum ChannelBookmarkType {
+        #[default]
+        Link = "link",
+        File = "file",
+    }
+}
+
+string_enum! {
+    /// Purpose of a file upload session.
+    pub enum UploadType {
+        Attachment = "attachment",
+        Import = "import",
+    }
+}
 
 /// Error struct from Mattermost.
 ///
@@ -13,11 +189,24 @@ use serde::Deserialize;
 pub struct MattermostError {
     pub id: String,
     pub message: String,
+    #[serde(default)]
+    pub detailed_error: String,
+    /// Not included in errors sent through the websocket API.
+    #[serde(default)]
     pub request_id: String,
     pub status_code: i16,
+    #[serde(default)]
     pub is_oauth: bool,
 }
 
+impl MattermostError {
+    /// Whether logging in failed because the multi-factor
+    /// authentication code was missing, wrong, or expired.
+    pub fn is_invalid_mfa_token(&self) -> bool {
+        self.id == "mfa.validate_token.authenticate.app_error"
+    }
+}
+
 /// Response struct from /teams/name/{name}
 #[derive(Debug, Deserialize)]
 pub struct TeamInformation {
@@ -30,13 +219,111 @@ pub struct TeamInformation {
     pub description: String,
     pub email: String,
     #[serde(rename = "type")]
-    pub type_: String,
+    pub type_: TeamType,
     pub allowed_domains: String,
     pub invite_id: String,
     pub allow_open_invite: bool,
     pub policy_id: Option<String>,
 }
 
+/// Request struct for creating a team.
+#[derive(Debug, Serialize)]
+pub struct TeamCreateBody {
+    /// The team's URL slug, e.g. "engineering".
+    pub name: String,
+    pub display_name: String,
+    #[serde(rename = "type")]
+    pub type_: TeamType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company_name: Option<String>,
+    /// Comma-separated email domains that can join, e.g. "example.com".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_domains: Option<String>,
+}
+
+impl TeamCreateBody {
+    /// Create a struct instance with only the required fields set.
+    pub fn new(name: impl Into<String>, display_name: impl Into<String>, type_: TeamType) -> Self {
+        Self {
+            name: name.into(),
+            display_name: display_name.into(),
+            type_,
+            description: None,
+            company_name: None,
+            allowed_domains: None,
+        }
+    }
+}
+
+/// Request struct for replacing a team's editable fields.
+#[derive(Debug, Serialize)]
+pub struct TeamUpdateBody {
+    /// Must match the id of the team being updated.
+    pub id: String,
+    pub display_name: String,
+    pub description: String,
+    pub company_name: String,
+    pub allowed_domains: String,
+    pub invite_id: String,
+    pub allow_open_invite: bool,
+}
+
+/// Request struct for changing some of a team's fields.
+///
+/// Only fields that are set are sent, so the others are left as they are.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TeamPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invite_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_open_invite: Option<bool>,
+}
+
+/// Response struct from /teams/invite/{invite_id}
+#[derive(Debug, Deserialize)]
+pub struct TeamInviteInfo {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub description: String,
+}
+
+/// A user's membership in a team.
+#[derive(Debug, Deserialize)]
+pub struct TeamMember {
+    pub team_id: String,
+    pub user_id: String,
+    /// Space-separated role names, e.g. "team_user team_admin".
+    #[serde(default)]
+    pub roles: String,
+    #[serde(default)]
+    pub delete_at: i64,
+    #[serde(default)]
+    pub scheme_guest: bool,
+    #[serde(default)]
+    pub scheme_user: bool,
+    #[serde(default)]
+    pub scheme_admin: bool,
+    #[serde(default)]
+    pub explicit_roles: String,
+}
+
+/// Response struct from /teams/{team_id}/stats
+#[derive(Debug, Deserialize)]
+pub struct TeamStats {
+    pub team_id: String,
+    pub total_member_count: u64,
+    pub active_member_count: u64,
+}
+
 /// Response struct from /users/{user_id}/teams/unread
 #[derive(Debug, Deserialize)]
 pub struct TeamsUnreadInformation {
@@ -45,8 +332,2288 @@ pub struct TeamsUnreadInformation {
     pub mention_count: u64,
 }
 
+/// Response struct from /users/{user_id}/channels/{channel_id}/unread
+#[derive(Debug, Deserialize)]
+pub struct ChannelUnread {
+    pub team_id: String,
+    pub channel_id: String,
+    pub msg_count: u64,
+    pub mention_count: u64,
+}
+
+/// Response struct from /channels/members/{user_id}/view
+#[derive(Debug, Deserialize)]
+pub struct ChannelViewResponse {
+    pub status: String,
+    /// When each viewed channel was last viewed, by channel id.
+    #[serde(default)]
+    pub last_viewed_at_times: HashMap<String, i64>,
+}
+
 /// Information about a single channel on the instance.
+///
+/// Direct and group message channels have an empty `team_id`.
 #[derive(Debug, Deserialize)]
 pub struct ChannelInformation {
-    //
+    pub id: String,
+    pub create_at: i64,
+    pub update_at: i64,
+    pub delete_at: i64,
+    pub team_id: String,
+    #[serde(rename = "type")]
+    pub type_: ChannelType,
+    pub display_name: String,
+    pub name: String,
+    #[serde(default)]
+    pub header: String,
+    #[serde(default)]
+    pub purpose: String,
+    #[serde(default)]
+    pub last_post_at: i64,
+    #[serde(default)]
+    pub total_msg_count: u64,
+    #[serde(default)]
+    pub creator_id: String,
+    pub extra_update_at: Option<i64>,
+    pub last_root_post_at: Option<i64>,
+    pub total_msg_count_root: Option<u64>,
+    pub scheme_id: Option<String>,
+    pub group_constrained: Option<bool>,
+    pub shared: Option<bool>,
+    pub policy_id: Option<String>,
+}
+
+/// Request struct for /channels/search
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ChannelSearchBody {
+    /// Matched against channel names and display names.
+    pub term: String,
+    /// Only channels in these teams.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub team_ids: Vec<String>,
+    /// Only public channels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public: Option<bool>,
+    /// Only private channels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private: Option<bool>,
+    /// Only archived channels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<bool>,
+    /// Leave out each team's default channels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_default_channels: Option<bool>,
+    /// When either of `page` or `per_page` is set,
+    /// the response includes the total number of matches.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u64>,
+}
+
+/// Response struct from /channels/search
+#[derive(Debug, Deserialize)]
+#[serde(from = "ChannelSearchResponse")]
+pub struct ChannelSearchResults {
+    pub channels: Vec<ChannelInformation>,
+    /// Number of matching channels across all pages.
+    /// Only sent when the search was paged.
+    pub total_count: Option<i64>,
+}
+
+/// The two shapes /channels/search responds with.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChannelSearchResponse {
+    Paged {
+        #[serde(default)]
+        channels: Vec<ChannelInformation>,
+        total_count: i64,
+    },
+    Unpaged(Vec<ChannelInformation>),
+}
+
+impl From<ChannelSearchResponse> for ChannelSearchResults {
+    fn from(response: ChannelSearchResponse) -> Self {
+        match response {
+            ChannelSearchResponse::Paged {
+                channels,
+                total_count,
+            } => Self {
+                channels,
+                total_count: Some(total_count),
+            },
+            ChannelSearchResponse::Unpaged(channels) => Self {
+                channels,
+                total_count: None,
+            },
+        }
+    }
+}
+
+/// Request struct for creating a channel.
+#[derive(Debug, Serialize)]
+pub struct ChannelCreateBody {
+    pub team_id: String,
+    /// The channel's URL slug, e.g. "town-square".
+    pub name: String,
+    pub display_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    /// Either `ChannelType::Open` or `ChannelType::Private`.
+    #[serde(rename = "type")]
+    pub type_: ChannelType,
+}
+
+/// Request struct for replacing a channel's editable fields.
+#[derive(Debug, Serialize)]
+pub struct ChannelUpdateBody {
+    /// Must match the id of the channel being updated.
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub purpose: String,
+    pub header: String,
+}
+
+/// Request struct for changing some of a channel's fields.
+///
+/// Only fields that are set are sent, so the others are left as they are.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChannelPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+}
+
+/// A link or file bookmarked at the top of a channel.
+#[derive(Debug, Deserialize)]
+pub struct ChannelBookmark {
+    pub id: String,
+    pub channel_id: String,
+    /// The user who created the bookmark.
+    pub owner_id: String,
+    pub display_name: String,
+    /// Only set on link bookmarks.
+    pub link_url: Option<String>,
+    /// Only set on file bookmarks.
+    pub file_id: Option<String>,
+    pub emoji: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: ChannelBookmarkType,
+    #[serde(default)]
+    pub sort_order: i64,
+    pub create_at: i64,
+    pub update_at: i64,
+    #[serde(default)]
+    pub delete_at: i64,
+}
+
+/// Request struct for creating a channel bookmark.
+#[derive(Debug, Default, Serialize)]
+pub struct ChannelBookmarkCreateBody {
+    pub display_name: String,
+    #[serde(rename = "type")]
+    pub type_: ChannelBookmarkType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_url: Option<String>,
+    /// Id of an already uploaded file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+}
+
+impl ChannelBookmarkCreateBody {
+    /// Create a struct instance for a bookmark of a link.
+    pub fn link(display_name: impl Into<String>, link_url: impl Into<String>) -> Self {
+        Self {
+            display_name: display_name.into(),
+            type_: ChannelBookmarkType::Link,
+            link_url: Some(link_url.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Create a struct instance for a bookmark of an uploaded file.
+    pub fn file(display_name: impl Into<String>, file_id: impl Into<String>) -> Self {
+        Self {
+            display_name: display_name.into(),
+            type_: ChannelBookmarkType::File,
+            file_id: Some(file_id.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Request struct for changing some of a channel bookmark's fields.
+///
+/// Only fields that are set are sent, so the others are left as they are.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChannelBookmarkPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+}
+
+/// Response struct from updating a channel bookmark.
+///
+/// Updating a bookmark that was created by someone else replaces it
+/// with a copy, in which case the original is returned as `deleted`.
+#[derive(Debug, Deserialize)]
+pub struct ChannelBookmarkUpdate {
+    pub updated: ChannelBookmark,
+    pub deleted: Option<ChannelBookmark>,
+}
+
+/// A user's membership in a channel.
+#[derive(Debug, Deserialize)]
+pub struct ChannelMember {
+    pub channel_id: String,
+    pub user_id: String,
+    /// Space-separated role names, e.g. "channel_user channel_admin".
+    #[serde(default)]
+    pub roles: String,
+    #[serde(default)]
+    pub last_viewed_at: i64,
+    #[serde(default)]
+    pub msg_count: u64,
+    #[serde(default)]
+    pub mention_count: u64,
+    pub notify_props: Option<ChannelNotifyProps>,
+    #[serde(default)]
+    pub last_update_at: i64,
+}
+
+/// Response struct from /channels/{channel_id}/stats
+#[derive(Debug, Deserialize)]
+pub struct ChannelStats {
+    pub channel_id: String,
+    pub member_count: u64,
+    #[serde(default)]
+    pub guest_count: u64,
+    #[serde(default)]
+    pub pinnedpost_count: u64,
+    #[serde(default)]
+    pub files_count: u64,
+}
+
+/// A user's notification settings for a single channel.
+///
+/// When updating, only the settings that are set are sent,
+/// and the rest are left unchanged on the server.
+///
+/// # Example
+///
+/// ```rust
+/// use mattermost_api::models::{ChannelNotifyProps, NotifyLevel};
+/// let muted = ChannelNotifyProps {
+///     desktop: Some(NotifyLevel::None),
+///     push: Some(NotifyLevel::None),
+///     mark_unread: Some(NotifyLevel::Mention),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelNotifyProps {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desktop: Option<NotifyLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<NotifyLevel>,
+    /// Which messages mark the channel as unread: "all" or "mention".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mark_unread: Option<NotifyLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub push: Option<NotifyLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_channel_mentions: Option<IgnoreChannelMentions>,
+}
+
+/// Response struct from endpoints that only report whether they succeeded.
+#[derive(Debug, Deserialize)]
+pub struct StatusResponse {
+    pub status: String,
+}
+
+/// Response struct from /users/stats
+#[derive(Debug, Deserialize)]
+pub struct UsersStats {
+    pub total_users_count: u64,
+}
+
+/// Information about a user on the instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserInformation {
+    pub id: String,
+    pub username: String,
+    /// Not included when the instance is configured to hide email addresses.
+    pub email: Option<String>,
+    #[serde(default)]
+    pub nickname: String,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub last_name: String,
+    #[serde(default)]
+    pub roles: String,
+    #[serde(default)]
+    pub locale: String,
+    pub create_at: i64,
+    pub update_at: i64,
+    pub delete_at: i64,
+    pub notify_props: Option<UserNotifyProps>,
+    pub timezone: Option<UserTimezone>,
+}
+
+/// A user's login session.
+#[derive(Debug, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub create_at: i64,
+    /// When the session expires, in milliseconds since the epoch.
+    pub expires_at: i64,
+    #[serde(default)]
+    pub last_activity_at: i64,
+    #[serde(default)]
+    pub user_id: String,
+    /// Set for sessions from the mobile apps.
+    #[serde(default)]
+    pub device_id: String,
+    #[serde(default)]
+    pub roles: String,
+    #[serde(default)]
+    pub is_oauth: bool,
+}
+
+/// A personal access token for a user.
+#[derive(Debug, Deserialize)]
+pub struct UserAccessToken {
+    pub id: String,
+    /// The token itself, only returned when it's created.
+    pub token: Option<String>,
+    pub user_id: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub is_active: bool,
+}
+
+/// A bot account.
+///
+/// The bot's `user_id` is also the id of the user it posts as.
+#[derive(Debug, Deserialize)]
+pub struct Bot {
+    pub user_id: String,
+    pub username: String,
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    /// The user or plugin that owns the bot.
+    #[serde(default)]
+    pub owner_id: String,
+    pub create_at: i64,
+    pub update_at: i64,
+    /// Nonzero when the bot has been disabled.
+    pub delete_at: i64,
+}
+
+/// Request struct for changing some of a bot's fields.
+///
+/// Only fields that are set are sent, so the others are left as they are.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BotPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A group of users, synced from LDAP or created on the instance.
+#[derive(Debug, Deserialize)]
+pub struct Group {
+    pub id: String,
+    /// Used to mention the group. Not set on LDAP groups
+    /// that haven't been made mentionable.
+    pub name: Option<String>,
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    pub source: GroupSource,
+    /// Id of the group in its source. Not set on custom groups.
+    pub remote_id: Option<String>,
+    pub create_at: i64,
+    pub update_at: i64,
+    pub delete_at: i64,
+    /// Whether the group is linked to any teams or channels.
+    #[serde(default)]
+    pub has_syncables: bool,
+    /// Only sent when requested.
+    pub member_count: Option<i64>,
+    /// Whether the group can be mentioned by its name.
+    #[serde(default)]
+    pub allow_reference: bool,
+}
+
+/// Response struct from /teams/{team_id}/groups and /channels/{channel_id}/groups
+#[derive(Debug, Deserialize)]
+pub struct GroupList {
+    #[serde(default)]
+    pub groups: Vec<Group>,
+    #[serde(default)]
+    pub total_group_count: i64,
+}
+
+/// Response struct from /groups/{group_id}/members
+#[derive(Debug, Deserialize)]
+pub struct GroupMembers {
+    #[serde(default)]
+    pub members: Vec<UserInformation>,
+    /// Number of members across all pages.
+    #[serde(default)]
+    pub total_member_count: i64,
+}
+
+/// A role, and the permissions it grants.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Role {
+    pub id: String,
+    /// e.g. "system_user" or "team_admin".
+    pub name: String,
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Permission names, e.g. "create_post".
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Whether the role is managed by a permissions scheme
+    /// rather than assigned directly.
+    #[serde(default)]
+    pub scheme_managed: bool,
+    #[serde(default)]
+    pub built_in: bool,
+    #[serde(default)]
+    pub create_at: i64,
+    #[serde(default)]
+    pub update_at: i64,
+    #[serde(default)]
+    pub delete_at: i64,
+}
+
+/// Where a permission is checked, for
+/// `Mattermost::current_user_has_permission`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionScope {
+    /// Only the user's system roles.
+    System,
+    /// The user's roles in the team with this id, and their system roles.
+    Team(String),
+    /// The user's roles in the channel with this id, in its team,
+    /// and their system roles.
+    Channel(String),
+}
+
+/// A user's notification settings.
+///
+/// The server sends these as strings, e.g. "true" or "mention".
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserNotifyProps {
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub push: String,
+    #[serde(default)]
+    pub desktop: String,
+    #[serde(default)]
+    pub desktop_sound: String,
+    /// Comma-separated words that trigger mentions.
+    #[serde(default)]
+    pub mention_keys: String,
+    #[serde(default)]
+    pub channel: String,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub comments: String,
+}
+
+/// A user's timezone settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserTimezone {
+    /// "true" if `automatic_timezone` is in use.
+    #[serde(rename = "useAutomaticTimezone", default)]
+    pub use_automatic_timezone: String,
+    #[serde(rename = "manualTimezone", default)]
+    pub manual_timezone: String,
+    #[serde(rename = "automaticTimezone", default)]
+    pub automatic_timezone: String,
+}
+
+/// Request struct for creating a post.
+///
+/// Create with `PostBody::new` and set optional fields with the
+/// chainable methods; only fields that are set are sent.
+#[derive(Debug, Default, Serialize)]
+#[non_exhaustive]
+pub struct PostBody {
+    pub channel_id: String,
+    pub message: String,
+    /// Set to the id of a post to reply to it in a thread.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_id: Option<String>,
+    /// Ids of files from `upload_file` to attach to the post.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_ids: Option<Vec<String>>,
+    /// Left out when nothing in it is set, as older servers reject it.
+    #[serde(skip_serializing_if = "PostBodyMetadata::is_unset")]
+    pub metadata: Option<PostBodyMetadata>,
+    /// Arbitrary properties, e.g. `attachments`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub props: Option<serde_json::Value>,
+}
+
+impl PostBody {
+    /// Create a struct instance with only the required fields set.
+    pub fn new(channel_id: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            channel_id: channel_id.into(),
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Post as a reply in the thread of the post with this id.
+    pub fn root_id(mut self, root_id: impl Into<String>) -> Self {
+        self.root_id = Some(root_id.into());
+        self
+    }
+
+    /// Attach files from `upload_file`.
+    pub fn file_ids(mut self, file_ids: Vec<String>) -> Self {
+        self.file_ids = Some(file_ids);
+        self
+    }
+
+    /// Set the post's props, replacing any set before.
+    pub fn props(mut self, props: serde_json::Value) -> Self {
+        self.props = Some(props);
+        self
+    }
+
+    /// Set the post's priority.
+    pub fn priority(mut self, priority: PostPriority) -> Self {
+        self.metadata.get_or_insert_with(Default::default).priority = Some(priority);
+        self
+    }
+
+    /// Set the post's message attachments, keeping any other props.
+    pub fn with_attachments(mut self, attachments: Vec<MessageAttachment>) -> Self {
+        let attachments = serde_json::to_value(attachments).unwrap_or_default();
+        match &mut self.props {
+            Some(serde_json::Value::Object(props)) => {
+                props.insert("attachments".to_owned(), attachments);
+            }
+            props => *props = Some(serde_json::json!({ "attachments": attachments })),
+        }
+        self
+    }
+
+    /// Check the post for combinations of fields that the server rejects.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if let Some(priority) = self.metadata.as_ref().and_then(|m| m.priority.as_ref()) {
+            if self.root_id.is_some() {
+                return Err(ApiError::InvalidPostPriority(
+                    "priority can only be set on root posts",
+                ));
+            }
+            priority.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// A rich "message attachment", shown below a post's message.
+///
+/// Only fields that are set are sent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessageAttachment {
+    /// Plain text summary for clients that can't show attachments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<String>,
+    /// Color of the sidebar, as a hex code like "#FF8000".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Text shown above the attachment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pretext: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_link: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_link: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<AttachmentField>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumb_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footer_icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<AttachmentAction>,
+}
+
+impl MessageAttachment {
+    /// Create a struct instance with only the text set.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Set the plain text summary.
+    pub fn fallback(mut self, fallback: impl Into<String>) -> Self {
+        self.fallback = Some(fallback.into());
+        self
+    }
+
+    /// Set the sidebar color.
+    pub fn color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// Set the text shown above the attachment.
+    pub fn pretext(mut self, pretext: impl Into<String>) -> Self {
+        self.pretext = Some(pretext.into());
+        self
+    }
+
+    /// Set the author block, with an optional link and icon URL.
+    pub fn author(
+        mut self,
+        name: impl Into<String>,
+        link: Option<String>,
+        icon: Option<String>,
+    ) -> Self {
+        self.author_name = Some(name.into());
+        self.author_link = link;
+        self.author_icon = icon;
+        self
+    }
+
+    /// Set the title, with an optional link.
+    pub fn title(mut self, title: impl Into<String>, link: Option<String>) -> Self {
+        self.title = Some(title.into());
+        self.title_link = link;
+        self
+    }
+
+    /// Add a field to the table of fields.
+    pub fn field(mut self, field: AttachmentField) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Set the URL of an image to show.
+    pub fn image_url(mut self, url: impl Into<String>) -> Self {
+        self.image_url = Some(url.into());
+        self
+    }
+
+    /// Set the URL of a thumbnail to show on the right.
+    pub fn thumb_url(mut self, url: impl Into<String>) -> Self {
+        self.thumb_url = Some(url.into());
+        self
+    }
+
+    /// Set the footer, with an optional icon URL.
+    pub fn footer(mut self, footer: impl Into<String>, icon: Option<String>) -> Self {
+        self.footer = Some(footer.into());
+        self.footer_icon = icon;
+        self
+    }
+
+    /// Add an interactive button or menu.
+    pub fn action(mut self, action: AttachmentAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+}
+
+/// A field in a message attachment's table of fields.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachmentField {
+    pub title: String,
+    /// Markdown text; may also be a number.
+    pub value: serde_json::Value,
+    /// Show the field next to other short fields instead of on its own line.
+    #[serde(default)]
+    pub short: bool,
+}
+
+impl AttachmentField {
+    /// Create a field shown on its own line.
+    pub fn new(title: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            value: serde_json::Value::String(value.into()),
+            short: false,
+        }
+    }
+
+    /// Create a field shown next to other short fields.
+    pub fn short(title: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            short: true,
+            ..Self::new(title, value)
+        }
+    }
+}
+
+/// An interactive button or menu in a message attachment.
+///
+/// Clicking it sends a request to the integration URL.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachmentAction {
+    /// Must be alphanumeric.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    /// "button" or "select".
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    /// "default", "primary", "success", "good", "warning", "danger",
+    /// or a hex color, for buttons.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    /// "users" or "channels" to fill a menu automatically.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_source: Option<String>,
+    /// Choices for a menu.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<AttachmentActionOption>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integration: Option<AttachmentActionIntegration>,
+}
+
+impl AttachmentAction {
+    /// Create a button that sends a request to `url` when clicked.
+    pub fn button(id: impl Into<String>, name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self::new("button", id, name, url)
+    }
+
+    /// Create a menu that sends a request to `url` when a choice is made.
+    pub fn select(id: impl Into<String>, name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self::new("select", id, name, url)
+    }
+
+    fn new(
+        type_: &str,
+        id: impl Into<String>,
+        name: impl Into<String>,
+        url: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Some(id.into()),
+            name: name.into(),
+            type_: Some(type_.to_owned()),
+            integration: Some(AttachmentActionIntegration {
+                url: url.into(),
+                context: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Set data to send to the integration URL along with the request.
+    pub fn context(mut self, context: serde_json::Value) -> Self {
+        if let Some(integration) = &mut self.integration {
+            integration.context = Some(context);
+        }
+        self
+    }
+
+    /// Set the button style.
+    pub fn style(mut self, style: impl Into<String>) -> Self {
+        self.style = Some(style.into());
+        self
+    }
+
+    /// Add a choice to a menu.
+    pub fn option(mut self, text: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.push(AttachmentActionOption {
+            text: text.into(),
+            value: value.into(),
+        });
+        self
+    }
+}
+
+/// A choice in an attachment's menu.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachmentActionOption {
+    pub text: String,
+    pub value: String,
+}
+
+/// Where an attachment action sends its request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachmentActionIntegration {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+}
+
+/// Request struct for replacing a post's editable fields.
+#[derive(Debug, Default, Serialize)]
+pub struct PostUpdateBody {
+    /// Must match the id of the post being updated.
+    pub id: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_pinned: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub props: Option<serde_json::Value>,
+}
+
+/// Request struct for changing some of a post's fields.
+///
+/// Only fields that are set are sent, so the others are left as they are.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PostPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_pinned: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_reactions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub props: Option<serde_json::Value>,
+}
+
+/// Metadata that can be set when creating a post.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PostBodyMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<PostPriority>,
+}
+
+impl PostBodyMetadata {
+    /// Whether there's no metadata, or none of its fields are set.
+    fn is_unset(metadata: &Option<Self>) -> bool {
+        match metadata {
+            Some(metadata) => metadata.priority.is_none(),
+            None => true,
+        }
+    }
+}
+
+string_enum! {
+    /// Priority level of a post.
+    #[derive(Default)]
+    pub enum PriorityLevel {
+        #[default]
+        Standard = "",
+        Important = "important",
+        Urgent = "urgent",
+    }
+}
+
+/// Priority of a post, and whether recipients are asked to acknowledge it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PostPriority {
+    #[serde(default)]
+    pub priority: PriorityLevel,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_ack: Option<bool>,
+    /// Repeatedly notify recipients until they acknowledge the post.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persistent_notifications: Option<bool>,
+}
+
+impl PostPriority {
+    /// Create a struct instance with the priority level and nothing else set.
+    pub fn new(priority: PriorityLevel) -> Self {
+        Self {
+            priority,
+            ..Default::default()
+        }
+    }
+
+    /// Check for combinations of settings that the server rejects.
+    ///
+    /// Persistent notifications require urgent priority and a requested acknowledgement.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.persistent_notifications == Some(true) {
+            if self.priority != PriorityLevel::Urgent {
+                return Err(ApiError::InvalidPostPriority(
+                    "persistent notifications require urgent priority",
+                ));
+            }
+            if self.requested_ack != Some(true) {
+                return Err(ApiError::InvalidPostPriority(
+                    "persistent notifications require a requested acknowledgement",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Metadata of a post, as returned by the server.
+///
+/// Lists the server has nothing to put in are omitted, and so empty here.
+#[derive(Debug, Deserialize)]
+pub struct PostMetadata {
+    pub priority: Option<PostPriority>,
+    /// Link previews, images, and message attachments in the post.
+    #[serde(default)]
+    pub embeds: Vec<PostEmbed>,
+    /// Custom emojis used in the post or its reactions.
+    #[serde(default)]
+    pub emojis: Vec<Emoji>,
+    /// Files attached to the post.
+    #[serde(default)]
+    pub files: Vec<FileInfo>,
+    #[serde(default)]
+    pub reactions: Vec<Reaction>,
+    /// Acknowledgements of posts that requested them.
+    #[serde(default)]
+    pub acknowledgements: Vec<PostAcknowledgement>,
+    /// Dimensions of images in the post, by URL.
+    #[serde(default)]
+    pub images: HashMap<String, PostImage>,
+}
+
+/// Something shown with a post's message, like a link preview.
+#[derive(Debug, Deserialize)]
+pub struct PostEmbed {
+    /// "image", "message_attachment", "opengraph", or "permalink".
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(default)]
+    pub url: String,
+    /// Depends on the type, e.g. OpenGraph data for "opengraph".
+    pub data: Option<serde_json::Value>,
+}
+
+/// Dimensions of an image in a post.
+#[derive(Debug, Deserialize)]
+pub struct PostImage {
+    pub width: u32,
+    pub height: u32,
+    /// e.g. "png".
+    #[serde(default)]
+    pub format: String,
+    /// Number of frames, for animated images.
+    #[serde(default)]
+    pub frame_count: u32,
+}
+
+/// A custom emoji.
+#[derive(Debug, Deserialize)]
+pub struct Emoji {
+    pub id: String,
+    #[serde(default)]
+    pub creator_id: String,
+    pub name: String,
+    pub create_at: i64,
+    pub update_at: i64,
+    pub delete_at: i64,
+}
+
+/// A post (message) in a channel.
+#[derive(Debug, Deserialize)]
+pub struct Post {
+    pub id: String,
+    pub create_at: i64,
+    pub update_at: i64,
+    #[serde(default)]
+    pub edit_at: i64,
+    pub delete_at: i64,
+    pub user_id: String,
+    pub channel_id: String,
+    #[serde(default)]
+    pub root_id: String,
+    #[serde(default)]
+    pub original_id: String,
+    pub message: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(default)]
+    pub hashtags: String,
+    #[serde(default)]
+    pub pending_post_id: String,
+    /// Arbitrary properties, e.g. `attachments`.
+    pub props: Option<serde_json::Value>,
+    pub is_pinned: Option<bool>,
+    /// Number of replies in the thread, if the post is in one.
+    pub reply_count: Option<i64>,
+    pub last_reply_at: Option<i64>,
+    /// Users who replied in the thread, if requested.
+    pub participants: Option<Vec<SanitizedUser>>,
+    /// Not included by older servers.
+    pub metadata: Option<PostMetadata>,
+}
+
+/// A user as sent to other users, with fields removed according to
+/// the instance's privacy settings.
+///
+/// This is the shape of users in websocket events such as
+/// `user_updated`. `email` is only present if the instance shows email
+/// addresses, and `first_name`/`last_name` if it shows full names,
+/// unless the event is being sent to that user themselves. The
+/// `notify_props`, `last_password_update`, `auth_service`, and
+/// `timezone` fields are only present when the event is sent to that
+/// user themselves.
+#[derive(Debug, Deserialize)]
+pub struct SanitizedUser {
+    pub id: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    #[serde(default)]
+    pub nickname: String,
+    #[serde(default)]
+    pub position: String,
+    #[serde(default)]
+    pub roles: String,
+    #[serde(default)]
+    pub locale: String,
+    #[serde(default)]
+    pub create_at: i64,
+    #[serde(default)]
+    pub update_at: i64,
+    /// Nonzero when the user has been deactivated.
+    #[serde(default)]
+    pub delete_at: i64,
+    #[serde(default)]
+    pub is_bot: bool,
+    pub notify_props: Option<HashMap<String, String>>,
+    pub last_password_update: Option<i64>,
+    pub auth_service: Option<String>,
+    pub timezone: Option<HashMap<String, String>>,
+}
+
+impl SanitizedUser {
+    /// If the user has been deactivated.
+    pub fn is_deactivated(&self) -> bool {
+        self.delete_at != 0
+    }
+}
+
+/// A single user preference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Preference {
+    pub user_id: String,
+    pub category: String,
+    pub name: String,
+    pub value: String,
+}
+
+/// Response struct from endpoints returning a list of posts.
+#[derive(Debug, Deserialize)]
+pub struct PostList {
+    /// Ids of the posts, in display order (newest first).
+    #[serde(default)]
+    pub order: Vec<String>,
+    /// Posts keyed by their id.
+    #[serde(default)]
+    pub posts: HashMap<String, Post>,
+    #[serde(default)]
+    pub next_post_id: String,
+    #[serde(default)]
+    pub prev_post_id: String,
+    #[serde(default)]
+    pub has_next: bool,
+}
+
+impl PostList {
+    /// The posts in the order given by `order`.
+    pub fn ordered_posts(&self) -> Vec<&Post> {
+        self.order
+            .iter()
+            .filter_map(|id| self.posts.get(id))
+            .collect()
+    }
+}
+
+/// Posts of a channel that changed after a time, oldest first.
+///
+/// See `Mattermost::sync_channel_posts`.
+#[derive(Debug, Default)]
+pub struct ChannelPostsSync {
+    /// Posts created after the time.
+    pub created: Vec<Post>,
+    /// Posts created before the time that were edited or deleted after
+    /// it. These keep their original `create_at`; check `edit_at`
+    /// and `delete_at` for what changed.
+    pub changed: Vec<Post>,
+    /// The time to pass as `since` for the next sync: the latest
+    /// `update_at` of the posts, or the time synced from if nothing
+    /// changed. When the result isn't `complete`, the latest time
+    /// every change up to is known to be covered.
+    pub next_since: i64,
+    /// Whether `changed` has every post edited or deleted after the
+    /// time. When the server capped its response some may be missing;
+    /// syncing on from `next_since` asks for them again, but if the
+    /// channel keeps changing faster than the cap, reload it instead.
+    pub complete: bool,
+}
+
+/// Response struct from /teams/{team_id}/posts/search
+#[derive(Debug, Deserialize)]
+pub struct PostSearchResults {
+    /// Ids of the matching posts, most relevant first.
+    #[serde(default)]
+    pub order: Vec<String>,
+    /// Posts keyed by their id.
+    #[serde(default)]
+    pub posts: HashMap<String, Post>,
+    /// Matched snippets of each post, keyed by post id.
+    /// Not sent by servers without search highlighting.
+    #[serde(default)]
+    pub matches: Option<HashMap<String, Vec<String>>>,
+}
+
+/// Request struct for /teams/{team_id}/posts/search
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct SearchPostsOptions {
+    /// The user's offset from UTC in seconds, for date filters like "on:".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone_offset: Option<i32>,
+    /// Also search archived channels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_deleted_channels: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u64>,
+}
+
+/// Options for /channels/{channel_id}/posts
+#[derive(Debug, Default, Clone)]
+pub struct GetPostsOptions {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    /// Only posts created, edited, or deleted after this time, in
+    /// milliseconds since the epoch. When set, paging is ignored.
+    pub since: Option<i64>,
+    /// Only posts before the post with this id.
+    pub before: Option<String>,
+    /// Only posts after the post with this id.
+    pub after: Option<String>,
+}
+
+/// Options for /users/{user_id}/teams/{team_id}/threads
+#[derive(Debug, Default, Clone)]
+pub struct GetUserThreadsOptions {
+    pub page: Option<u64>,
+    /// Sent as `pageSize`.
+    pub page_size: Option<u64>,
+    /// Only threads updated after this time, in milliseconds since the epoch.
+    pub since: Option<i64>,
+    /// Include threads whose root post has been deleted.
+    pub deleted: Option<bool>,
+    /// Only threads with unread replies.
+    pub unread: Option<bool>,
+    /// Return full users as participants, rather than only their ids.
+    pub extended: Option<bool>,
+    /// Only the totals, without any threads.
+    pub totals_only: Option<bool>,
+}
+
+/// A user taking part in a thread.
+///
+/// Only `id` is set unless the thread was requested with `extended`.
+#[derive(Debug, Deserialize)]
+pub struct ThreadParticipant {
+    pub id: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub nickname: String,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub last_name: String,
+}
+
+/// A thread that a user follows.
+#[derive(Debug, Deserialize)]
+pub struct UserThread {
+    /// The id of the thread's root post.
+    pub id: String,
+    pub reply_count: i64,
+    pub last_reply_at: i64,
+    pub last_viewed_at: i64,
+    #[serde(default)]
+    pub participants: Vec<ThreadParticipant>,
+    /// The thread's root post.
+    pub post: Post,
+    pub unread_replies: i64,
+    pub unread_mentions: i64,
+    #[serde(default)]
+    pub is_urgent: bool,
+    #[serde(default)]
+    pub delete_at: i64,
+}
+
+/// Response struct from /users/{user_id}/teams/{team_id}/threads
+#[derive(Debug, Deserialize)]
+pub struct UserThreads {
+    pub total: i64,
+    #[serde(default)]
+    pub total_unread_threads: i64,
+    #[serde(default)]
+    pub total_unread_mentions: i64,
+    #[serde(default)]
+    pub total_unread_urgent_mentions: i64,
+    #[serde(default)]
+    pub threads: Vec<UserThread>,
+}
+
+/// The `AnnouncementSettings` section of the server config,
+/// which controls the instance-wide announcement banner.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AnnouncementSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_banner: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner_text: Option<String>,
+    /// Background color of the banner, e.g. "#f2a93b".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner_text_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_banner_dismissal: Option<bool>,
+}
+
+impl AnnouncementSettings {
+    /// Whether every field set here has the same value in `other`.
+    pub(crate) fn is_applied_in(&self, other: &Self) -> bool {
+        fn same<T: PartialEq>(sent: &Option<T>, got: &Option<T>) -> bool {
+            sent.is_none() || sent == got
+        }
+        same(&self.enable_banner, &other.enable_banner)
+            && same(&self.banner_text, &other.banner_text)
+            && same(&self.banner_color, &other.banner_color)
+            && same(&self.banner_text_color, &other.banner_text_color)
+            && same(&self.allow_banner_dismissal, &other.allow_banner_dismissal)
+    }
+}
+
+/// Request and response struct for /config/patch, limited to
+/// the announcement banner section.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AnnouncementConfigPatch {
+    #[serde(rename = "AnnouncementSettings", default)]
+    pub announcement_settings: AnnouncementSettings,
+}
+
+/// A plugin's manifest.
+///
+/// Manifests vary a lot between plugins, so the settings schema and
+/// the server and webapp sections are kept as plain JSON.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginManifest {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub min_server_version: String,
+    #[serde(default)]
+    pub homepage_url: String,
+    #[serde(default)]
+    pub support_url: String,
+    #[serde(default)]
+    pub settings_schema: Option<serde_json::Value>,
+    #[serde(default)]
+    pub server: Option<serde_json::Value>,
+    #[serde(default)]
+    pub webapp: Option<serde_json::Value>,
+}
+
+/// Response struct from /plugins.
+#[derive(Debug, Deserialize)]
+pub struct Plugins {
+    #[serde(default)]
+    pub active: Vec<PluginManifest>,
+    #[serde(default)]
+    pub inactive: Vec<PluginManifest>,
+}
+
+/// Whether a plugin is running on a server.
+///
+/// The server sends these as numbers; values not known to this
+/// library deserialize into `Unknown` rather than failing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluginState {
+    NotRunning,
+    Starting,
+    Running,
+    FailedToStart,
+    FailedToStayRunning,
+    Stopping,
+    /// A value not known to this library.
+    Unknown(i64),
+}
+
+impl From<i64> for PluginState {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::NotRunning,
+            1 => Self::Starting,
+            2 => Self::Running,
+            3 => Self::FailedToStart,
+            4 => Self::FailedToStayRunning,
+            5 => Self::Stopping,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PluginState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from(i64::deserialize(deserializer)?))
+    }
+}
+
+/// The status of a plugin on one server of the cluster.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginStatus {
+    pub plugin_id: String,
+    #[serde(default)]
+    pub cluster_id: String,
+    #[serde(default)]
+    pub plugin_path: String,
+    pub state: PluginState,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub version: String,
+}
+
+/// Information about an uploaded file.
+#[derive(Debug, Deserialize)]
+pub struct FileInfo {
+    pub id: String,
+    #[serde(default)]
+    pub user_id: String,
+    /// Empty until the file is attached to a post.
+    #[serde(default)]
+    pub post_id: String,
+    pub create_at: i64,
+    pub update_at: i64,
+    pub delete_at: i64,
+    pub name: String,
+    #[serde(default)]
+    pub extension: String,
+    pub size: u64,
+    #[serde(default)]
+    pub mime_type: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub has_preview_image: bool,
+}
+
+/// Response struct from /files
+#[derive(Debug, Deserialize)]
+pub struct FileUploadResponse {
+    pub file_infos: Vec<FileInfo>,
+    #[serde(default)]
+    pub client_ids: Vec<String>,
+}
+
+/// An incoming webhook, for posting to a channel from another service.
+#[derive(Debug, Deserialize)]
+pub struct IncomingWebhook {
+    pub id: String,
+    pub create_at: i64,
+    pub update_at: i64,
+    pub delete_at: i64,
+    pub channel_id: String,
+    #[serde(default)]
+    pub team_id: String,
+    #[serde(default)]
+    pub user_id: String,
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Overrides the name posts are shown under, if allowed.
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub icon_url: String,
+    /// If posts can only go to `channel_id`.
+    #[serde(default)]
+    pub channel_locked: bool,
+}
+
+/// Request struct for creating an incoming webhook.
+#[derive(Debug, Default, Serialize)]
+pub struct IncomingWebhookCreateBody {
+    pub channel_id: String,
+    /// The user to post as, if not the logged in user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_locked: Option<bool>,
+}
+
+/// An outgoing webhook, for sending posts in a channel to another service.
+#[derive(Debug, Deserialize)]
+pub struct OutgoingWebhook {
+    pub id: String,
+    /// Sent with each request, for the service to verify it.
+    #[serde(default)]
+    pub token: String,
+    pub create_at: i64,
+    pub update_at: i64,
+    pub delete_at: i64,
+    #[serde(default)]
+    pub creator_id: String,
+    pub team_id: String,
+    /// Empty if the webhook isn't limited to one channel.
+    #[serde(default)]
+    pub channel_id: String,
+    #[serde(default)]
+    pub trigger_words: Vec<String>,
+    /// 0 if a post's first word must be a trigger word,
+    /// 1 if it only has to start with one.
+    #[serde(default)]
+    pub trigger_when: i32,
+    #[serde(default)]
+    pub callback_urls: Vec<String>,
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    /// "application/x-www-form-urlencoded" or "application/json".
+    #[serde(default)]
+    pub content_type: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub icon_url: String,
+}
+
+/// Request struct for creating an outgoing webhook.
+#[derive(Debug, Default, Serialize)]
+pub struct OutgoingWebhookCreateBody {
+    pub team_id: String,
+    pub display_name: String,
+    pub trigger_words: Vec<String>,
+    pub callback_urls: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger_when: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+}
+
+/// Request struct for posting to an incoming webhook.
+#[derive(Debug, Default, Serialize)]
+pub struct IncomingWebhookPayload {
+    pub text: String,
+    /// Overrides the name the post is shown under, if allowed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// Name of a channel, or "@username" for a direct message,
+    /// to post to instead of the webhook's channel, if allowed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<MessageAttachment>>,
+}
+
+/// A custom slash command.
+#[derive(Debug, Deserialize)]
+pub struct Command {
+    pub id: String,
+    /// Sent with each request to the command's URL, to verify it's from the instance.
+    #[serde(default)]
+    pub token: String,
+    pub create_at: i64,
+    pub update_at: i64,
+    pub delete_at: i64,
+    #[serde(default)]
+    pub creator_id: String,
+    pub team_id: String,
+    /// The word after the slash, e.g. "echo" for "/echo".
+    pub trigger: String,
+    #[serde(default)]
+    pub method: CommandMethod,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub icon_url: String,
+    #[serde(default)]
+    pub auto_complete: bool,
+    #[serde(default)]
+    pub auto_complete_desc: String,
+    #[serde(default)]
+    pub auto_complete_hint: String,
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Request struct for creating a slash command.
+#[derive(Debug, Default, Serialize)]
+pub struct CommandCreateBody {
+    pub team_id: String,
+    pub trigger: String,
+    pub url: String,
+    pub method: CommandMethod,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// Show the command in the autocomplete list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_complete: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_complete_desc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_complete_hint: Option<String>,
+}
+
+impl CommandCreateBody {
+    /// Create a struct instance with only the required fields set,
+    /// requesting the URL with POST.
+    pub fn new(
+        team_id: impl Into<String>,
+        trigger: impl Into<String>,
+        url: impl Into<String>,
+    ) -> Self {
+        Self {
+            team_id: team_id.into(),
+            trigger: trigger.into(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Response struct from /commands/execute
+///
+/// Which fields are set depends on the command.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CommandResponse {
+    pub response_type: CommandResponseType,
+    pub text: String,
+    /// Overrides the name the response is posted under, if allowed.
+    pub username: String,
+    pub icon_url: String,
+    /// Where the client should navigate to, e.g. a channel's URL.
+    pub goto_location: String,
+    /// Sent as null by some commands.
+    pub attachments: Option<Vec<MessageAttachment>>,
+}
+
+/// A response from the instance API, before any deserialization.
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: u16,
+    pub headers: reqwest::header::HeaderMap,
+    pub bytes: Bytes,
+}
+
+impl RawResponse {
+    /// If the server responded that the resource hasn't changed since
+    /// the version with the ETag sent in `If-None-Match`.
+    pub fn is_not_modified(&self) -> bool {
+        self.status == 304
+    }
+
+    /// The value of the `ETag` header, to send as `If-None-Match`
+    /// to later requests for the same resource.
+    pub fn etag(&self) -> Option<&str> {
+        self.headers
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// Deserialize the body as JSON.
+    ///
+    /// With the `strict-models` feature, keys that `T` doesn't
+    /// have are an error; see `ApiError::UnknownFields`.
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, ApiError> {
+        from_json_slice(&self.bytes)
+    }
+
+    /// Deserialize the body as JSON, along with the paging
+    /// information from the `X-Total-Count` and `X-Has-Next` headers.
+    pub fn paged<T: serde::de::DeserializeOwned>(&self) -> Result<Paged<T>, ApiError> {
+        let header = |name: &str| {
+            self.headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+        };
+        Ok(Paged {
+            body: self.json()?,
+            total_count: header("X-Total-Count").and_then(|value| value.parse().ok()),
+            has_next: header("X-Has-Next").and_then(|value| value.parse().ok()),
+        })
+    }
+}
+
+/// Deserialize a JSON response body into a model.
+///
+/// With the `strict-models` feature, keys in the body that the model
+/// drops are logged and returned as `ApiError::UnknownFields`.
+pub(crate) fn from_json_slice<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, ApiError> {
+    #[cfg(feature = "strict-models")]
+    {
+        let mut unknown = Vec::new();
+        let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+        let value =
+            serde_ignored::deserialize(&mut deserializer, |path| unknown.push(path.to_string()))?;
+        deserializer.end()?;
+        if !unknown.is_empty() {
+            log::warn!(
+                "Response has fields {:?} that {} drops",
+                unknown,
+                std::any::type_name::<T>()
+            );
+            return Err(ApiError::UnknownFields(unknown));
+        }
+        Ok(value)
+    }
+    #[cfg(not(feature = "strict-models"))]
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+/// A page of a list response, along with the paging
+/// information the server sent in the response headers.
+#[derive(Debug)]
+pub struct Paged<T> {
+    pub body: T,
+    /// The number of items across all pages, if the server sent it.
+    pub total_count: Option<u64>,
+    /// Whether there are more pages, if the server sent it.
+    pub has_next: Option<bool>,
+}
+
+/// A list response deserialized element by element, with
+/// the elements that couldn't be deserialized left out.
+#[derive(Debug)]
+pub struct LossyList<T> {
+    /// Elements that were deserialized, in order.
+    pub items: Vec<T>,
+    /// Position in the response and error of each element that wasn't.
+    pub errors: Vec<(usize, serde_json::Error)>,
+}
+
+/// Image data returned from the instance API, along with
+/// the content type the server reported for it.
+#[derive(Debug, Clone)]
+pub struct ImageBytes {
+    /// The value of the response's `Content-Type` header,
+    /// e.g. "image/png".
+    pub content_type: String,
+    pub bytes: Bytes,
+    /// The value of the response's `ETag` header, if any, to check
+    /// later whether the image has changed.
+    pub etag: Option<String>,
+}
+
+/// A reaction to a post.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reaction {
+    pub user_id: String,
+    pub post_id: String,
+    pub emoji_name: String,
+    #[serde(default)]
+    pub create_at: i64,
+}
+
+/// A user's acknowledgement of a post that requested it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostAcknowledgement {
+    pub user_id: String,
+    pub post_id: String,
+    /// When the post was acknowledged, in milliseconds since the epoch.
+    pub acknowledged_at: i64,
+}
+
+string_enum! {
+    /// Presence status of a user.
+    pub enum PresenceStatus {
+        Online = "online",
+        Away = "away",
+        Offline = "offline",
+        Dnd = "dnd",
+    }
+}
+
+/// Presence status of a user.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserStatus {
+    pub user_id: String,
+    pub status: PresenceStatus,
+    /// If the status was set by the user, rather than from their activity.
+    #[serde(default)]
+    pub manual: bool,
+    #[serde(default)]
+    pub last_activity_at: i64,
+    /// When a do not disturb status ends, in seconds since the epoch.
+    pub dnd_end_time: Option<i64>,
+}
+
+/// Request struct for /users/{user_id}/status
+#[derive(Debug, Clone, Serialize)]
+pub struct UserStatusUpdate {
+    pub user_id: String,
+    pub status: PresenceStatus,
+    /// When a do not disturb status should end, in seconds since the epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dnd_end_time: Option<i64>,
+}
+
+/// Response struct from /oauth/access_token, for both exchanging an
+/// authorization code and refreshing a token.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OAuthTokenResponse {
+    pub access_token: String,
+    /// "bearer".
+    pub token_type: String,
+    /// Seconds until the access token expires.
+    #[serde(default)]
+    pub expires_in: i64,
+    /// Used with `Mattermost::refresh_oauth_token` to get a new access token.
+    #[serde(default)]
+    pub refresh_token: String,
+    #[serde(default)]
+    pub scope: String,
+}
+
+/// Response struct from /system/ping
+///
+/// The mobile app versions are empty unless configured on the instance,
+/// and may be missing from older servers.
+#[derive(Debug, Deserialize)]
+pub struct SystemStatus {
+    pub status: String,
+    #[serde(rename = "AndroidLatestVersion")]
+    pub android_latest_version: Option<String>,
+    #[serde(rename = "AndroidMinVersion")]
+    pub android_min_version: Option<String>,
+    #[serde(rename = "IosLatestVersion")]
+    pub ios_latest_version: Option<String>,
+    #[serde(rename = "IosMinVersion")]
+    pub ios_min_version: Option<String>,
+}
+
+/// Request struct for /users/search
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct UserSearchBody {
+    /// Matched against usernames, full names, nicknames, and emails.
+    pub term: String,
+    /// Only users in this team.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<String>,
+    /// Only users not in this team.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_in_team_id: Option<String>,
+    /// Only users in this channel.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_channel_id: Option<String>,
+    /// Only users not in this channel.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_in_channel_id: Option<String>,
+    /// Include deactivated users.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_inactive: Option<bool>,
+    /// Defaults to 100 on the server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+/// Response struct from /users/autocomplete
+#[derive(Debug, Deserialize)]
+pub struct UserAutocomplete {
+    /// Users matching the name, in the channel if one was given.
+    #[serde(default)]
+    pub users: Vec<UserInformation>,
+    /// Users matching the name in the team but not in the channel,
+    /// if a channel was given.
+    #[serde(default)]
+    pub out_of_channel: Vec<UserInformation>,
+}
+
+/// Request struct for creating a user.
+#[derive(Debug, Serialize)]
+pub struct UserCreateBody {
+    pub email: String,
+    pub username: String,
+    pub password: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+impl UserCreateBody {
+    /// Create a struct instance with only the required fields set.
+    pub fn new(
+        email: impl Into<String>,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            email: email.into(),
+            username: username.into(),
+            password: password.into(),
+            first_name: None,
+            last_name: None,
+            nickname: None,
+            locale: None,
+        }
+    }
+}
+
+/// Request struct for /system/onboarding/complete
+#[derive(Debug, Default, Serialize)]
+pub struct CompleteOnboardingBody {
+    pub organization: String,
+    pub install_plugins: Vec<String>,
+}
+
+/// Version of a Mattermost server, as reported in the `X-Version-Id` response header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ServerVersion {
+    /// Create a struct instance from its components.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse the leading `major.minor.patch` out of an `X-Version-Id` header value,
+    /// which has the form `7.8.0.7.8.0.<build hash>.<enterprise>`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split('.').map(|part| part.parse::<u32>().ok());
+        Some(Self::new(parts.next()??, parts.next()??, parts.next()??))
+    }
+}
+
+impl std::fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_version_header_is_parsed() {
+        assert_eq!(
+            ServerVersion::parse("7.8.0.7.8.0.e5fb4b5b0fe9dd4f6bad9e0bc4d4c5e4.false"),
+            Some(ServerVersion::new(7, 8, 0))
+        );
+        assert_eq!(
+            ServerVersion::parse("10.0.1.10.0.1.2e7bd5a4f1f0d0b0c5b3d1c8e1f5a5b2.true"),
+            Some(ServerVersion::new(10, 0, 1))
+        );
+        assert_eq!(ServerVersion::parse("not a version"), None);
+    }
+
+    #[cfg(feature = "strict-models")]
+    #[test]
+    fn strict_models_report_dropped_fields() {
+        let body = br#"{"user_id": "u", "post_id": "p", "emoji_name": "+1",
+            "create_at": 1, "remote_id": "", "props": {"a": 1}}"#;
+        match from_json_slice::<Reaction>(body) {
+            Err(ApiError::UnknownFields(fields)) => {
+                assert_eq!(fields, vec!["remote_id", "props"])
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        let body = br#"{"user_id": "u", "post_id": "p", "emoji_name": "+1"}"#;
+        assert!(from_json_slice::<Reaction>(body).is_ok());
+    }
+
+    #[test]
+    fn string_enums_round_trip_known_values() {
+        let channel_type: ChannelType = serde_json::from_str(r#""P""#).unwrap();
+        assert_eq!(channel_type, ChannelType::Private);
+        assert_eq!(serde_json::to_string(&channel_type).unwrap(), r#""P""#);
+
+        let status: JobStatus = serde_json::from_str(r#""in_progress""#).unwrap();
+        assert_eq!(status, JobStatus::InProgress);
+        assert_eq!(status.to_string(), "in_progress");
+    }
+
+    #[test]
+    fn string_enums_accept_unknown_values() {
+        let channel_type: ChannelType = serde_json::from_str(r#""X""#).unwrap();
+        assert_eq!(channel_type, ChannelType::Unknown("X".to_owned()));
+        assert_eq!(serde_json::to_string(&channel_type).unwrap(), r#""X""#);
+
+        let scope: SchemeScope = serde_json::from_str(r#""playbook""#).unwrap();
+        assert_eq!(scope, SchemeScope::Unknown("playbook".to_owned()));
+
+        let upload: UploadType = serde_json::from_str(r#""avatar""#).unwrap();
+        assert_eq!(upload.as_str(), "avatar");
+
+        let statuses: Vec<JobStatus> =
+            serde_json::from_str(r#"["success", "paused", "error"]"#).unwrap();
+        assert_eq!(
+            statuses,
+            vec![
+                JobStatus::Success,
+                JobStatus::Unknown("paused".to_owned()),
+                JobStatus::Error
+            ]
+        );
+    }
+
+    fn post_body(root_id: Option<&str>, priority: Option<PostPriority>) -> PostBody {
+        let mut body = PostBody::new("c", "m");
+        if let Some(root_id) = root_id {
+            body = body.root_id(root_id);
+        }
+        if let Some(priority) = priority {
+            body = body.priority(priority);
+        }
+        body
+    }
+
+    #[test]
+    fn post_priority_is_serialized_only_when_set() {
+        assert_eq!(
+            serde_json::to_value(post_body(None, None)).unwrap(),
+            serde_json::json!({"channel_id": "c", "message": "m"})
+        );
+        let mut empty_metadata = post_body(None, None);
+        empty_metadata.metadata = Some(PostBodyMetadata::default());
+        assert_eq!(
+            serde_json::to_value(empty_metadata).unwrap(),
+            serde_json::json!({"channel_id": "c", "message": "m"})
+        );
+        let priority = PostPriority {
+            priority: PriorityLevel::Urgent,
+            requested_ack: Some(true),
+            persistent_notifications: Some(true),
+        };
+        assert_eq!(
+            serde_json::to_value(post_body(None, Some(priority))).unwrap(),
+            serde_json::json!({"channel_id": "c", "message": "m", "metadata": {"priority": {
+                "priority": "urgent", "requested_ack": true, "persistent_notifications": true
+            }}})
+        );
+    }
+
+    #[test]
+    fn post_priority_combinations_are_validated() {
+        let urgent_ack_persistent = PostPriority {
+            priority: PriorityLevel::Urgent,
+            requested_ack: Some(true),
+            persistent_notifications: Some(true),
+        };
+        assert!(post_body(None, Some(urgent_ack_persistent.clone()))
+            .validate()
+            .is_ok());
+        assert!(post_body(Some("root"), Some(urgent_ack_persistent.clone()))
+            .validate()
+            .is_err());
+        assert!(
+            post_body(None, Some(PostPriority::new(PriorityLevel::Important)))
+                .validate()
+                .is_ok()
+        );
+
+        let important_persistent = PostPriority {
+            priority: PriorityLevel::Important,
+            ..urgent_ack_persistent.clone()
+        };
+        assert!(important_persistent.validate().is_err());
+        let no_ack_persistent = PostPriority {
+            requested_ack: None,
+            ..urgent_ack_persistent
+        };
+        assert!(no_ack_persistent.validate().is_err());
+    }
+
+    #[test]
+    fn post_priority_is_read_from_post_metadata() {
+        let post: Post = serde_json::from_str(
+            r#"{"id": "p", "create_at": 1, "update_at": 1, "delete_at": 0, "user_id": "u",
+                "channel_id": "c", "message": "m", "type": "",
+                "metadata": {"priority": {"priority": "urgent", "requested_ack": true}}}"#,
+        )
+        .unwrap();
+        let priority = post.metadata.unwrap().priority.unwrap();
+        assert_eq!(priority.priority, PriorityLevel::Urgent);
+        assert_eq!(priority.requested_ack, Some(true));
+        assert_eq!(priority.persistent_notifications, None);
+
+        let old_server_post: Post = serde_json::from_str(
+            r#"{"id": "p", "create_at": 1, "update_at": 1, "delete_at": 0, "user_id": "u",
+                "channel_id": "c", "message": "m", "type": ""}"#,
+        )
+        .unwrap();
+        assert!(old_server_post.metadata.is_none());
+    }
+
+    #[test]
+    fn post_metadata_is_read() {
+        let post: Post = serde_json::from_str(
+            r#"{
+                "id": "p", "create_at": 1, "update_at": 1, "edit_at": 0, "delete_at": 0,
+                "is_pinned": true, "user_id": "u", "channel_id": "c", "root_id": "",
+                "original_id": "", "message": "see ![chart](https://example.com/c.png)",
+                "type": "", "props": {}, "hashtags": "", "pending_post_id": "",
+                "reply_count": 2, "last_reply_at": 5, "participants": null,
+                "metadata": {
+                    "embeds": [{"type": "image", "url": "https://example.com/c.png"}],
+                    "emojis": [{"id": "e", "creator_id": "u", "name": "party_parrot",
+                        "create_at": 1, "update_at": 1, "delete_at": 0}],
+                    "files": [{"id": "f", "user_id": "u", "post_id": "p", "create_at": 1,
+                        "update_at": 1, "delete_at": 0, "name": "report.pdf",
+                        "extension": "pdf", "size": 1024, "mime_type": "application/pdf",
+                        "width": 0, "height": 0, "has_preview_image": false}],
+                    "reactions": [{"user_id": "u2", "post_id": "p",
+                        "emoji_name": "party_parrot", "create_at": 2}],
+                    "images": {"https://example.com/c.png": {"width": 640, "height": 480,
+                        "format": "png", "frame_count": 0}}
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(post.is_pinned, Some(true));
+        assert_eq!(post.reply_count, Some(2));
+        assert_eq!(post.last_reply_at, Some(5));
+        assert!(post.participants.is_none());
+        let metadata = post.metadata.unwrap();
+        assert_eq!(metadata.embeds[0].type_, "image");
+        assert_eq!(metadata.emojis[0].name, "party_parrot");
+        assert_eq!(metadata.files[0].name, "report.pdf");
+        assert_eq!(metadata.files[0].size, 1024);
+        assert_eq!(metadata.reactions[0].user_id, "u2");
+        assert_eq!(metadata.images["https://example.com/c.png"].width, 640);
+        assert!(metadata.priority.is_none());
+    }
+
+    #[test]
+    fn post_list_deserializes_posts_map() {
+        let list: PostList = serde_json::from_str(
+            r#"{"order": ["p2", "p1"], "posts": {
+                "p1": {"id": "p1", "create_at": 1, "update_at": 1, "delete_at": 0,
+                       "user_id": "u", "channel_id": "c", "message": "first", "type": ""},
+                "p2": {"id": "p2", "create_at": 2, "update_at": 2, "delete_at": 0,
+                       "user_id": "u", "channel_id": "c", "message": "second", "type": ""}},
+                "next_post_id": "", "prev_post_id": "p0", "has_next": false}"#,
+        )
+        .unwrap();
+        assert_eq!(list.posts.len(), 2);
+        assert_eq!(list.posts["p1"].message, "first");
+        assert_eq!(list.prev_post_id, "p0");
+        let messages: Vec<&str> = list
+            .ordered_posts()
+            .iter()
+            .map(|p| p.message.as_str())
+            .collect();
+        assert_eq!(messages, vec!["second", "first"]);
+    }
+
+    #[test]
+    fn channel_information_deserializes_open_channel() {
+        let channel: ChannelInformation = serde_json::from_str(
+            r#"{"id": "4xp9fdt77pncbef59f4k1qe83o", "create_at": 1583850823034,
+                "update_at": 1583850823034, "delete_at": 0,
+                "team_id": "ag5f1zhhyjfe8jqbkxj7dtxbhh", "type": "O",
+                "display_name": "Town Square", "name": "town-square",
+                "header": "Welcome!", "purpose": "", "last_post_at": 1700000000000,
+                "total_msg_count": 42, "extra_update_at": 0, "creator_id": "",
+                "scheme_id": null, "props": null, "group_constrained": null,
+                "shared": false, "total_msg_count_root": 40, "policy_id": null,
+                "last_root_post_at": 1700000000000, "banner_info": {"enabled": false}}"#,
+        )
+        .unwrap();
+        assert_eq!(channel.type_, ChannelType::Open);
+        assert_eq!(channel.name, "town-square");
+        assert_eq!(channel.total_msg_count, 42);
+        assert_eq!(channel.scheme_id, None);
+        assert_eq!(channel.shared, Some(false));
+    }
+
+    #[test]
+    fn channel_information_deserializes_direct_channel() {
+        let channel: ChannelInformation = serde_json::from_str(
+            r#"{"id": "dm", "create_at": 1, "update_at": 1, "delete_at": 0,
+                "team_id": "", "type": "D", "display_name": "",
+                "name": "user1__user2", "header": "", "purpose": "",
+                "last_post_at": 2, "total_msg_count": 3, "creator_id": "user1"}"#,
+        )
+        .unwrap();
+        assert_eq!(channel.type_, ChannelType::Direct);
+        assert!(channel.team_id.is_empty());
+        assert_eq!(channel.policy_id, None);
+    }
+
+    #[test]
+    fn user_information_deserializes_props() {
+        let user: UserInformation = serde_json::from_str(
+            r#"{"id": "u", "username": "alice", "email": "alice@example.com",
+                "create_at": 1, "update_at": 2, "delete_at": 0,
+                "notify_props": {"email": "true", "push": "mention", "desktop": "all",
+                    "desktop_sound": "true", "mention_keys": "alice,@alice",
+                    "channel": "true", "first_name": "false", "comments": "never"},
+                "timezone": {"useAutomaticTimezone": "true", "manualTimezone": "",
+                    "automaticTimezone": "Europe/Berlin"}}"#,
+        )
+        .unwrap();
+        assert_eq!(user.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(user.notify_props.unwrap().mention_keys, "alice,@alice");
+        assert_eq!(user.timezone.unwrap().automatic_timezone, "Europe/Berlin");
+    }
+
+    #[test]
+    fn post_body_skips_unset_file_ids() {
+        let body = serde_json::to_value(post_body(None, None)).unwrap();
+        assert!(body.get("file_ids").is_none());
+
+        let body = PostBody::new("c", "").file_ids(vec!["f1".to_owned()]);
+        assert_eq!(
+            serde_json::to_value(body).unwrap(),
+            serde_json::json!({"channel_id": "c", "message": "", "file_ids": ["f1"]})
+        );
+    }
+
+    #[test]
+    fn attachments_are_serialized_without_unset_fields() {
+        let attachment = MessageAttachment::new("Build **failed**")
+            .color("#FF0000")
+            .field(AttachmentField::short("Branch", "main"))
+            .action(
+                AttachmentAction::button("retry", "Retry", "https://ci.example.com/retry")
+                    .context(serde_json::json!({"build": 12}))
+                    .style("danger"),
+            );
+        let body = PostBody::new("c", "")
+            .props(serde_json::json!({"from_bot": "true"}))
+            .with_attachments(vec![attachment]);
+        assert_eq!(
+            serde_json::to_value(body).unwrap(),
+            serde_json::json!({"channel_id": "c", "message": "", "props": {
+                "from_bot": "true",
+                "attachments": [{
+                    "color": "#FF0000",
+                    "text": "Build **failed**",
+                    "fields": [{"title": "Branch", "value": "main", "short": true}],
+                    "actions": [{
+                        "id": "retry", "name": "Retry", "type": "button", "style": "danger",
+                        "integration": {
+                            "url": "https://ci.example.com/retry",
+                            "context": {"build": 12}
+                        }
+                    }]
+                }]
+            }})
+        );
+
+        let post: Post = serde_json::from_str(
+            r#"{"id": "p", "create_at": 1, "update_at": 1, "delete_at": 0, "user_id": "u",
+                "channel_id": "c", "message": "", "type": "slack_attachment",
+                "props": {"attachments": [{"id": 1, "text": "hi", "fields": null}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(post.props.unwrap()["attachments"][0]["text"], "hi");
+    }
+
+    #[test]
+    fn post_patch_only_serializes_set_fields() {
+        let patch = PostPatch {
+            message: Some("edited".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&patch).unwrap(),
+            r#"{"message":"edited"}"#
+        );
+    }
 }
@@ -0,0 +1,82 @@
+//! Opt-in hooks to observe each request to the instance API.
+//!
+//! Set an observer with [`Mattermost::with_observer`] to log, trace,
+//! or record metrics for requests, including the `X-Request-Id` the
+//! server assigned each one, to find them in the server's logs.
+//!
+//! [`Mattermost::with_observer`]: ../client/struct.Mattermost.html#method.with_observer
+
+use std::time::Duration;
+
+/// Hooks called around each request to the instance API.
+///
+/// Both methods do nothing by default. They are called from the task
+/// making the request, so should return quickly.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mattermost_api::{
+///     observer::{RequestObserver, ResponseInfo},
+///     prelude::*,
+/// };
+///
+/// struct SlowRequestLogger;
+///
+/// impl RequestObserver for SlowRequestLogger {
+///     fn on_response(&self, info: &ResponseInfo) {
+///         if info.elapsed.as_secs() >= 1 {
+///             println!(
+///                 "{} {} took {:?} (request id {:?})",
+///                 info.method, info.endpoint, info.elapsed, info.request_id
+///             );
+///         }
+///     }
+/// }
+///
+/// # fn run() {
+/// let auth = AuthenticationData::from_access_token("token");
+/// let api = Mattermost::new("https://your-mattermost-instance.com", auth)
+///     .unwrap()
+///     .with_observer(SlowRequestLogger);
+/// # }
+/// ```
+pub trait RequestObserver: Send + Sync {
+    /// Called before each request is sent, including retries.
+    fn on_request(&self, _info: &RequestInfo) {}
+
+    /// Called when the response to a request arrives, before its body
+    /// is read, or when the request fails without a response.
+    fn on_response(&self, _info: &ResponseInfo) {}
+}
+
+impl std::fmt::Debug for dyn RequestObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RequestObserver")
+    }
+}
+
+/// A request about to be sent to the instance API.
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    /// HTTP method, e.g. "GET".
+    pub method: String,
+    /// The path relative to the API root, e.g. "channels/abc/posts".
+    pub endpoint: String,
+}
+
+/// The outcome of a request to the instance API.
+#[derive(Debug, Clone)]
+pub struct ResponseInfo {
+    /// HTTP method, e.g. "GET".
+    pub method: String,
+    /// The path relative to the API root, e.g. "channels/abc/posts".
+    pub endpoint: String,
+    /// The response's status code, or `None` if the request failed
+    /// without a response, such as when the connection failed.
+    pub status: Option<u16>,
+    /// Time from sending the request until the response headers arrived.
+    pub elapsed: Duration,
+    /// The `X-Request-Id` header of the response, if any.
+    pub request_id: Option<String>,
+}
@@ -1,6 +1,11 @@
 //! Module for easy imports.
 
-pub use crate::client::{AuthenticationData, Mattermost};
+pub use crate::client::{AuthMode, AuthenticationData, Mattermost, MattermostBuilder, QueryParams};
 pub use crate::errors::ApiError;
 pub use crate::models::MattermostError;
-pub use crate::socket::{WebsocketEvent, WebsocketHandler};
+pub use crate::retry::RetryPolicy;
+pub use crate::socket::{
+    Backpressure, HandlerError, ReconnectPolicy, WebsocketConnectionHandle, WebsocketEvent,
+    WebsocketEventsOptions, WebsocketHandler, WebsocketHandlerError, WebsocketReply,
+    WebsocketSender,
+};
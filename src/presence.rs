@@ -0,0 +1,237 @@
+//! Automatic presence status management for bot accounts.
+//!
+//! Bots holding a websocket connection otherwise show as online all
+//! the time. With a [`PresencePolicy`] set on the client, the bot's
+//! status follows its activity instead: it's set to away after a
+//! period without posts and back to online when posting resumes, and
+//! to do not disturb during a configured [`MaintenanceWindow`].
+//!
+//! Statuses set manually by someone else, such as an admin, are left
+//! alone.
+
+use crate::models::{PresenceStatus, UserStatus};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// How to manage the status of the logged in user.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum PresencePolicy {
+    /// Set the status to away once there have been no posts for
+    /// `idle_after`, and back to online when a post is made.
+    AwayWhenIdle {
+        #[allow(missing_docs)]
+        idle_after: Duration,
+    },
+}
+
+/// Daily period, in UTC, during which the status is set to do not disturb.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    start: u64,
+    end: u64,
+}
+
+impl MaintenanceWindow {
+    /// Create a window from its start and end as hours and minutes in UTC.
+    ///
+    /// The window may wrap past midnight, e.g. from 23:00 to 01:00.
+    ///
+    /// # Panics
+    ///
+    /// If an hour is over 23 or a minute is over 59.
+    pub fn daily_utc(start: (u8, u8), end: (u8, u8)) -> Self {
+        let seconds = |(hour, minute): (u8, u8)| {
+            assert!(hour < 24 && minute < 60, "invalid time of day");
+            u64::from(hour) * 3600 + u64::from(minute) * 60
+        };
+        Self {
+            start: seconds(start),
+            end: seconds(end),
+        }
+    }
+
+    /// If the window contains a time, given as seconds since midnight UTC.
+    pub(crate) fn contains(&self, seconds_since_midnight: u64) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&seconds_since_midnight)
+        } else {
+            seconds_since_midnight >= self.start || seconds_since_midnight < self.end
+        }
+    }
+}
+
+/// Tracks activity and the status last set, to decide on status changes.
+#[derive(Debug)]
+pub(crate) struct PresenceManager {
+    pub(crate) policy: Option<PresencePolicy>,
+    pub(crate) maintenance_window: Option<MaintenanceWindow>,
+    last_activity: Mutex<Instant>,
+    applied: Mutex<Option<PresenceStatus>>,
+}
+
+impl PresenceManager {
+    pub(crate) fn new() -> Self {
+        Self {
+            policy: None,
+            maintenance_window: None,
+            last_activity: Mutex::new(Instant::now()),
+            applied: Mutex::new(None),
+        }
+    }
+
+    /// How often to check whether the status needs to change.
+    pub(crate) fn check_interval(&self) -> Duration {
+        let idle_check = match &self.policy {
+            Some(PresencePolicy::AwayWhenIdle { idle_after }) => *idle_after / 4,
+            None => Duration::MAX,
+        };
+        idle_check
+            .min(Duration::from_secs(60))
+            .max(Duration::from_secs(1))
+    }
+
+    pub(crate) fn record_activity(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    pub(crate) fn applied(&self) -> Option<PresenceStatus> {
+        self.applied.lock().unwrap().clone()
+    }
+
+    pub(crate) fn set_applied(&self, status: PresenceStatus) {
+        *self.applied.lock().unwrap() = Some(status);
+    }
+
+    /// The status the user should have now.
+    pub(crate) fn desired_status(&self) -> Option<PresenceStatus> {
+        let since_midnight = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() % SECONDS_PER_DAY);
+        let idle_for = self.last_activity.lock().unwrap().elapsed();
+        desired_status(
+            self.policy.as_ref(),
+            self.maintenance_window.as_ref(),
+            idle_for,
+            since_midnight,
+        )
+    }
+}
+
+fn desired_status(
+    policy: Option<&PresencePolicy>,
+    maintenance_window: Option<&MaintenanceWindow>,
+    idle_for: Duration,
+    seconds_since_midnight: u64,
+) -> Option<PresenceStatus> {
+    if maintenance_window.is_some_and(|w| w.contains(seconds_since_midnight)) {
+        return Some(PresenceStatus::Dnd);
+    }
+    match policy? {
+        PresencePolicy::AwayWhenIdle { idle_after } if idle_for >= *idle_after => {
+            Some(PresenceStatus::Away)
+        }
+        PresencePolicy::AwayWhenIdle { .. } => Some(PresenceStatus::Online),
+    }
+}
+
+/// If the current status may be changed, given the status last set by the manager.
+///
+/// Statuses set through the API are always marked manual, so a manual
+/// status is only considered the manager's own if it matches what the
+/// manager last set. Before the manager has set anything, a manual
+/// online status is taken to be the default.
+pub(crate) fn may_change(current: &UserStatus, applied: Option<&PresenceStatus>) -> bool {
+    if !current.manual {
+        return true;
+    }
+    match applied {
+        Some(applied) => *applied == current.status,
+        None => current.status == PresenceStatus::Online,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDLE: PresencePolicy = PresencePolicy::AwayWhenIdle {
+        idle_after: Duration::from_secs(300),
+    };
+
+    fn status(status: PresenceStatus, manual: bool) -> UserStatus {
+        UserStatus {
+            user_id: "u".to_owned(),
+            status,
+            manual,
+            last_activity_at: 0,
+            dnd_end_time: None,
+        }
+    }
+
+    #[test]
+    fn maintenance_windows() {
+        let night = MaintenanceWindow::daily_utc((2, 0), (4, 30));
+        assert!(!night.contains(3600));
+        assert!(night.contains(2 * 3600));
+        assert!(night.contains(4 * 3600 + 29 * 60));
+        assert!(!night.contains(4 * 3600 + 30 * 60));
+
+        let wrapping = MaintenanceWindow::daily_utc((23, 0), (1, 0));
+        assert!(wrapping.contains(23 * 3600 + 30 * 60));
+        assert!(wrapping.contains(0));
+        assert!(!wrapping.contains(3600));
+        assert!(!wrapping.contains(22 * 3600));
+    }
+
+    #[test]
+    fn desired_status_follows_activity_and_maintenance() {
+        let window = MaintenanceWindow::daily_utc((2, 0), (3, 0));
+        let active = Duration::from_secs(10);
+        let idle = Duration::from_secs(600);
+        let noon = 12 * 3600;
+        let maintenance = 2 * 3600 + 60;
+
+        assert_eq!(
+            desired_status(Some(&IDLE), None, active, noon),
+            Some(PresenceStatus::Online)
+        );
+        assert_eq!(
+            desired_status(Some(&IDLE), None, idle, noon),
+            Some(PresenceStatus::Away)
+        );
+        assert_eq!(
+            desired_status(Some(&IDLE), Some(&window), active, maintenance),
+            Some(PresenceStatus::Dnd)
+        );
+        assert_eq!(
+            desired_status(None, Some(&window), idle, maintenance),
+            Some(PresenceStatus::Dnd)
+        );
+        assert_eq!(desired_status(None, Some(&window), idle, noon), None);
+    }
+
+    #[test]
+    fn manual_statuses_set_by_others_are_respected() {
+        // automatic statuses can always be changed
+        assert!(may_change(&status(PresenceStatus::Offline, false), None));
+        // the default online status can be changed before anything was set
+        assert!(may_change(&status(PresenceStatus::Online, true), None));
+        // an admin set dnd before anything was set
+        assert!(!may_change(&status(PresenceStatus::Dnd, true), None));
+        // the manager set away, and it's still away
+        assert!(may_change(
+            &status(PresenceStatus::Away, true),
+            Some(&PresenceStatus::Away)
+        ));
+        // the manager set away, and an admin changed it to dnd
+        assert!(!may_change(
+            &status(PresenceStatus::Dnd, true),
+            Some(&PresenceStatus::Away)
+        ));
+    }
+}
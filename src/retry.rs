@@ -0,0 +1,184 @@
+//! Retrying requests that were rate limited or failed on the server.
+
+use reqwest::{header::HeaderMap, Method, Response, StatusCode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Settings for retrying requests to the instance API.
+///
+/// Requests that got a 429 response are always retried, waiting for
+/// as long as the `Retry-After` header says if it's present, but no
+/// longer than `max_delay`. Requests
+/// that got a 5xx response or couldn't connect are retried only if
+/// they're idempotent (not POST or PATCH), unless
+/// `retry_non_idempotent` is set, since the server may have already
+/// processed them.
+///
+/// Without a `Retry-After` header, the wait before each retry doubles
+/// from `base_delay` up to `max_delay`, with random jitter.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Number of times to retry a request before returning the error.
+    pub max_retries: u32,
+    /// Wait before the first retry.
+    pub base_delay: Duration,
+    /// Longest wait between retries.
+    pub max_delay: Duration,
+    /// Also retry POST and PATCH requests after a 5xx response
+    /// or connection failure.
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retrying a request, or `None` if it shouldn't be retried.
+    ///
+    /// `retries` is how many times the request has been retried already.
+    pub(crate) fn delay(
+        &self,
+        method: &Method,
+        result: &Result<Response, reqwest::Error>,
+        retries: u32,
+    ) -> Option<Duration> {
+        if retries >= self.max_retries {
+            return None;
+        }
+        let idempotent = *method != Method::POST && *method != Method::PATCH;
+        let may_be_processed = idempotent || self.retry_non_idempotent;
+        match result {
+            Ok(resp) if resp.status() == StatusCode::TOO_MANY_REQUESTS => Some(
+                RateLimit::from_headers(resp.headers())
+                    .retry_after
+                    .map(|retry_after| retry_after.min(self.max_delay))
+                    .unwrap_or_else(|| self.backoff(retries)),
+            ),
+            Ok(resp) if resp.status().is_server_error() && may_be_processed => {
+                Some(self.backoff(retries))
+            }
+            Err(e) if (e.is_connect() || e.is_timeout()) && may_be_processed => {
+                Some(self.backoff(retries))
+            }
+            _ => None,
+        }
+    }
+
+    /// Exponential backoff with jitter of up to half the delay.
+    fn backoff(&self, retries: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retries))
+            .min(self.max_delay);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        let jitter = delay.mul_f64(f64::from(nanos % 1000) / 2000.0);
+        delay - jitter
+    }
+}
+
+/// Rate limiting information from the headers of a 429 response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Requests allowed per second, from `X-Ratelimit-Limit`.
+    pub limit: Option<u64>,
+    /// Requests remaining, from `X-Ratelimit-Remaining`.
+    pub remaining: Option<u64>,
+    /// Seconds until the limit resets, from `X-Ratelimit-Reset`.
+    pub reset: Option<u64>,
+    /// How long to wait before trying again, from `Retry-After`.
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimit {
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let number = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+        Self {
+            limit: number("X-Ratelimit-Limit"),
+            remaining: number("X-Ratelimit-Remaining"),
+            reset: number("X-Ratelimit-Reset"),
+            retry_after: number("Retry-After").map(Duration::from_secs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn backoff_doubles_up_to_max_with_jitter() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            retry_non_idempotent: false,
+        };
+        for (retries, full) in [(0, 100), (1, 200), (2, 400), (3, 500), (9, 500)] {
+            let full = Duration::from_millis(full);
+            let delay = policy.backoff(retries);
+            assert!(
+                delay <= full && delay >= full / 2,
+                "{delay:?} for {retries}"
+            );
+        }
+    }
+
+    #[test]
+    fn retry_after_is_capped_at_max_delay() {
+        let policy = RetryPolicy::default();
+        let rate_limited = |retry_after: &str| {
+            let resp = http::Response::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .header("Retry-After", retry_after)
+                .body("")
+                .unwrap();
+            Ok(Response::from(resp))
+        };
+        assert_eq!(
+            policy.delay(&Method::GET, &rate_limited("2"), 0),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            policy.delay(&Method::GET, &rate_limited("86400"), 0),
+            Some(policy.max_delay)
+        );
+    }
+
+    #[test]
+    fn rate_limit_headers_are_parsed() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Ratelimit-Limit", HeaderValue::from_static("10"));
+        headers.insert("X-Ratelimit-Remaining", HeaderValue::from_static("0"));
+        headers.insert("X-Ratelimit-Reset", HeaderValue::from_static("1"));
+        headers.insert("Retry-After", HeaderValue::from_static("2"));
+        assert_eq!(
+            RateLimit::from_headers(&headers),
+            RateLimit {
+                limit: Some(10),
+                remaining: Some(0),
+                reset: Some(1),
+                retry_after: Some(Duration::from_secs(2)),
+            }
+        );
+        assert_eq!(
+            RateLimit::from_headers(&HeaderMap::new()),
+            RateLimit::default()
+        );
+    }
+}
@@ -1,8 +1,26 @@
 //! Websocket client and trait for interacting with the websocket API.
 
+use crate::{
+    errors::ApiError,
+    models::{
+        ChannelType, MattermostError, Post, PostAcknowledgement, PresenceStatus, Reaction,
+        SanitizedUser,
+    },
+};
 use async_trait::async_trait;
+use async_tungstenite::tungstenite::Message;
+use log::debug;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::json;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{sync::mpsc, task::JoinHandle};
 
 /// Websocket event broadcast information
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,10 +29,20 @@ pub struct WebsocketEventBroadcast {
     pub omit_users: Option<HashMap<String, bool>>,
     /// Event recipient
     pub user_id: Option<String>,
-    #[allow(missing_docs)]
+    /// Channel the event was sent to, empty if not sent to a channel
+    #[serde(default)]
     pub channel_id: String,
-    #[allow(missing_docs)]
+    /// Team the event was sent to, empty if not sent to a team
+    #[serde(default)]
     pub team_id: String,
+    /// Connection the event was sent to, empty if not sent to a single
+    /// connection. Only sent by newer servers.
+    #[serde(default)]
+    pub connection_id: String,
+    /// Connection that was omitted from receiving the event, usually
+    /// the one that caused it. Only sent by newer servers.
+    #[serde(default)]
+    pub omit_connection_id: String,
 }
 
 /// Event data from the websocket API
@@ -30,6 +58,329 @@ pub struct WebsocketEvent {
     pub seq: usize,
 }
 
+/// Reply from the websocket API to an action sent by the client,
+/// such as the authentication challenge.
+#[derive(Debug, Deserialize)]
+pub struct WebsocketReply {
+    /// Sequence number of the action this is a reply to
+    pub seq_reply: usize,
+    /// "OK" if the action succeeded, or "FAIL"
+    pub status: String,
+    /// Data returned by the action, if any
+    pub data: Option<serde_json::Value>,
+    /// Why the action failed, if it did
+    pub error: Option<MattermostError>,
+}
+
+impl WebsocketReply {
+    /// Whether the action succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.status == "OK"
+    }
+}
+
+/// Text message from the websocket API.
+#[derive(Debug)]
+pub(crate) enum IncomingMessage {
+    Event(WebsocketEvent),
+    Reply(WebsocketReply),
+}
+
+impl IncomingMessage {
+    /// Parse a text message, telling replies apart from
+    /// events by whether they have a `seq_reply` field.
+    pub(crate) fn parse(text: &str) -> Result<Self, serde_json::Error> {
+        let value: serde_json::Value = serde_json::from_str(text)?;
+        if value.get("seq_reply").is_some() {
+            WebsocketReply::deserialize(value).map(Self::Reply)
+        } else {
+            WebsocketEvent::deserialize(value).map(Self::Event)
+        }
+    }
+}
+
+impl WebsocketEvent {
+    /// Parse the data of user lifecycle events.
+    ///
+    /// Returns `None` if this isn't a `user_updated`, `new_user`,
+    /// `user_added`, or `user_removed` event.
+    pub fn user_event(&self) -> Option<Result<UserEvent, serde_json::Error>> {
+        let parsed = match self.event.as_str() {
+            websocket_event_types::USER_UPDATED => UserUpdatedData::deserialize(&self.data)
+                .map(|d| UserEvent::Updated(Box::new(d.user))),
+            websocket_event_types::NEW_USER => {
+                UserIdData::deserialize(&self.data).map(|d| UserEvent::New { user_id: d.user_id })
+            }
+            websocket_event_types::USER_ADDED => {
+                UserAddedData::deserialize(&self.data).map(|d| UserEvent::AddedToChannel {
+                    user_id: d.user_id,
+                    channel_id: self.broadcast.channel_id.clone(),
+                    team_id: d.team_id,
+                })
+            }
+            websocket_event_types::USER_REMOVED => {
+                UserRemovedData::deserialize(&self.data).map(|d| UserEvent::RemovedFromChannel {
+                    // sent to the removed user themselves, the data holds the channel instead
+                    user_id: d
+                        .user_id
+                        .or_else(|| self.broadcast.user_id.clone())
+                        .unwrap_or_default(),
+                    channel_id: d
+                        .channel_id
+                        .unwrap_or_else(|| self.broadcast.channel_id.clone()),
+                    remover_id: d.remover_id,
+                })
+            }
+            _ => return None,
+        };
+        Some(parsed)
+    }
+
+    /// Parse the event's data into a typed payload.
+    ///
+    /// Events without a typed payload in this library are returned
+    /// as `WebsocketEventData::Other` holding the untyped data.
+    pub fn parsed_data(&self) -> Result<WebsocketEventData, serde_json::Error> {
+        use websocket_event_types as types;
+
+        Ok(match self.event.as_str() {
+            types::POSTED => {
+                WebsocketEventData::Posted(Box::new(PostedEvent::deserialize(&self.data)?))
+            }
+            types::POST_EDITED => {
+                WebsocketEventData::PostEdited(Box::new(PostData::deserialize(&self.data)?.post))
+            }
+            types::POST_DELETED => WebsocketEventData::PostDeleted(Box::new(
+                PostDeletedEvent::deserialize(&self.data)?,
+            )),
+            types::TYPING => WebsocketEventData::Typing(TypingEvent::deserialize(&self.data)?),
+            types::STATUS_CHANGE => {
+                WebsocketEventData::StatusChange(StatusChangeEvent::deserialize(&self.data)?)
+            }
+            types::REACTION_ADDED => {
+                WebsocketEventData::ReactionAdded(ReactionData::deserialize(&self.data)?.reaction)
+            }
+            types::REACTION_REMOVED => {
+                WebsocketEventData::ReactionRemoved(ReactionData::deserialize(&self.data)?.reaction)
+            }
+            types::USER_UPDATED => WebsocketEventData::UserUpdated(Box::new(
+                UserUpdatedData::deserialize(&self.data)?.user,
+            )),
+            types::EPHEMERAL_MESSAGE => WebsocketEventData::EphemeralMessage(Box::new(
+                PostData::deserialize(&self.data)?.post,
+            )),
+            types::POST_ACKNOWLEDGEMENT_ADDED => WebsocketEventData::PostAcknowledgementAdded(
+                AcknowledgementData::deserialize(&self.data)?.acknowledgement,
+            ),
+            types::POST_ACKNOWLEDGEMENT_REMOVED => WebsocketEventData::PostAcknowledgementRemoved(
+                AcknowledgementData::deserialize(&self.data)?.acknowledgement,
+            ),
+            _ => WebsocketEventData::Other(self.data.clone()),
+        })
+    }
+}
+
+/// Typed data of websocket events.
+#[derive(Debug)]
+pub enum WebsocketEventData {
+    /// `posted`
+    Posted(Box<PostedEvent>),
+    /// `post_edited`, holding the post after the edit.
+    PostEdited(Box<Post>),
+    /// `post_deleted`
+    PostDeleted(Box<PostDeletedEvent>),
+    /// `typing`
+    Typing(TypingEvent),
+    /// `status_change`
+    StatusChange(StatusChangeEvent),
+    /// `reaction_added`
+    ReactionAdded(Reaction),
+    /// `reaction_removed`
+    ReactionRemoved(Reaction),
+    /// `user_updated`
+    UserUpdated(Box<SanitizedUser>),
+    /// `ephemeral_message`, holding a post only the recipient can see.
+    EphemeralMessage(Box<Post>),
+    /// `post_acknowledgement_added`
+    PostAcknowledgementAdded(PostAcknowledgement),
+    /// `post_acknowledgement_removed`
+    PostAcknowledgementRemoved(PostAcknowledgement),
+    /// Any other event, with its untyped data.
+    Other(serde_json::Value),
+}
+
+/// Deserialize a value that the server sends as a string of encoded JSON.
+fn from_json_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let text = std::borrow::Cow::<str>::deserialize(deserializer)?;
+    serde_json::from_str(&text).map_err(serde::de::Error::custom)
+}
+
+/// Data of the `posted` event.
+#[derive(Debug, Deserialize)]
+pub struct PostedEvent {
+    #[allow(missing_docs)]
+    #[serde(deserialize_with = "from_json_string")]
+    pub post: Post,
+    #[allow(missing_docs)]
+    #[serde(default)]
+    pub channel_display_name: String,
+    #[allow(missing_docs)]
+    #[serde(default)]
+    pub channel_name: String,
+    #[allow(missing_docs)]
+    pub channel_type: Option<ChannelType>,
+    /// Display name of the poster, such as `@username`.
+    #[serde(default)]
+    pub sender_name: String,
+    /// Empty for direct and group messages.
+    #[serde(default)]
+    pub team_id: String,
+    /// Ids of the users mentioned in the post.
+    #[serde(default, deserialize_with = "from_json_string_or_default")]
+    pub mentions: Vec<String>,
+    /// If the poster's status should be set to online.
+    pub set_online: Option<bool>,
+}
+
+/// Like `from_json_string`, for fields that may also be absent.
+fn from_json_string_or_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned + Default,
+{
+    let text = Option::<std::borrow::Cow<str>>::deserialize(deserializer)?;
+    match text {
+        Some(text) if !text.is_empty() => {
+            serde_json::from_str(&text).map_err(serde::de::Error::custom)
+        }
+        _ => Ok(T::default()),
+    }
+}
+
+#[derive(Deserialize)]
+struct PostData {
+    #[serde(deserialize_with = "from_json_string")]
+    post: Post,
+}
+
+/// Data of the `post_deleted` event.
+#[derive(Debug, Deserialize)]
+pub struct PostDeletedEvent {
+    #[allow(missing_docs)]
+    #[serde(deserialize_with = "from_json_string")]
+    pub post: Post,
+    /// Id of the user who deleted the post, if it wasn't its author.
+    pub delete_by: Option<String>,
+}
+
+/// Data of the `typing` event.
+#[derive(Debug, Deserialize)]
+pub struct TypingEvent {
+    #[allow(missing_docs)]
+    pub user_id: String,
+    /// Id of the thread's root post when typing in a thread, otherwise empty.
+    #[serde(default)]
+    pub parent_id: String,
+}
+
+/// Data of the `status_change` event.
+#[derive(Debug, Deserialize)]
+pub struct StatusChangeEvent {
+    #[allow(missing_docs)]
+    pub user_id: String,
+    #[allow(missing_docs)]
+    pub status: PresenceStatus,
+}
+
+#[derive(Deserialize)]
+struct ReactionData {
+    #[serde(deserialize_with = "from_json_string")]
+    reaction: Reaction,
+}
+
+#[derive(Deserialize)]
+struct AcknowledgementData {
+    #[serde(deserialize_with = "from_json_string")]
+    acknowledgement: PostAcknowledgement,
+}
+
+/// Typed data of user lifecycle websocket events.
+#[derive(Debug)]
+pub enum UserEvent {
+    /// `user_updated`, including when a user is deactivated or reactivated.
+    Updated(Box<SanitizedUser>),
+    /// `new_user`, sent when a user account is created.
+    New {
+        #[allow(missing_docs)]
+        user_id: String,
+    },
+    /// `user_added`, sent when a user is added to a channel.
+    #[allow(missing_docs)]
+    AddedToChannel {
+        user_id: String,
+        channel_id: String,
+        team_id: String,
+    },
+    /// `user_removed`, sent when a user is removed from a channel.
+    #[allow(missing_docs)]
+    RemovedFromChannel {
+        user_id: String,
+        channel_id: String,
+        remover_id: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+struct UserUpdatedData {
+    user: SanitizedUser,
+}
+
+#[derive(Deserialize)]
+struct UserIdData {
+    user_id: String,
+}
+
+#[derive(Deserialize)]
+struct UserAddedData {
+    user_id: String,
+    #[serde(default)]
+    team_id: String,
+}
+
+#[derive(Deserialize)]
+struct UserRemovedData {
+    user_id: Option<String>,
+    channel_id: Option<String>,
+    remover_id: Option<String>,
+}
+
+/// Error returned by `WebsocketHandler::try_callback`.
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A websocket message that wasn't handled, passed to
+/// `WebsocketHandler::on_error`. The connection stays open.
+#[derive(Debug, thiserror::Error)]
+pub enum WebsocketHandlerError {
+    /// The message wasn't a valid event or reply.
+    #[error("Could not parse websocket message: {error}")]
+    Parse {
+        /// The message as received.
+        text: String,
+        /// Why it couldn't be parsed.
+        error: serde_json::Error,
+    },
+    /// `try_callback` returned an error.
+    #[error("Websocket handler failed: {0}")]
+    Callback(HandlerError),
+    /// The handler panicked, with this message.
+    #[error("Websocket handler panicked: {0}")]
+    Panic(String),
+}
+
 /// Handler trait for receiving websocket messages.
 ///
 /// Implement on a struct you create, and pass to
@@ -54,6 +405,164 @@ pub struct WebsocketEvent {
 pub trait WebsocketHandler: Send + Sync {
     /// Function to implement to receive websocket messages.
     async fn callback(&self, _message: WebsocketEvent) {}
+
+    /// Called with each event along with the exact text the server sent,
+    /// including any fields that `WebsocketEvent` doesn't keep.
+    ///
+    /// Calls `callback` unless this is implemented, so only implement one
+    /// of the two. The text is borrowed from the received message, so
+    /// handlers that don't need it don't pay for a copy.
+    async fn callback_raw(&self, message: WebsocketEvent, _raw: &str) {
+        self.callback(message).await;
+    }
+
+    /// Called with each event and its text like `callback_raw`, for
+    /// handlers that can fail. Errors are logged and passed to `on_error`,
+    /// and the connection keeps receiving events.
+    ///
+    /// Calls `callback_raw` unless this is implemented.
+    async fn try_callback(&self, message: WebsocketEvent, raw: &str) -> Result<(), HandlerError> {
+        self.callback_raw(message, raw).await;
+        Ok(())
+    }
+
+    /// Called when a message couldn't be handled, because it couldn't be
+    /// parsed, or the callback returned an error or panicked.
+    ///
+    /// Errors are only logged unless this is implemented.
+    async fn on_error(&self, _error: WebsocketHandlerError) {}
+
+    /// Called with replies to actions sent through the connection.
+    ///
+    /// The reply to the authentication challenge, which has sequence
+    /// number 1, is consumed while connecting and isn't passed here.
+    ///
+    /// Replies are ignored unless this is implemented.
+    async fn on_reply(&self, _reply: WebsocketReply) {}
+
+    /// Called after a connection to the websocket API is made, with a
+    /// handle for sending actions through that connection.
+    ///
+    /// When reconnecting, this is called for every new connection,
+    /// so it's a good place to catch up on events missed in between.
+    /// Senders for earlier connections stop working once they close.
+    async fn on_connect(&self, _sender: WebsocketSender) {}
+
+    /// Called after a connection to the websocket API is lost or closed.
+    async fn on_disconnect(&self) {}
+}
+
+/// Handle for sending actions through a websocket connection.
+///
+/// Cheap to clone; all clones share the connection's sequence numbers.
+/// Replies to actions are sent to `WebsocketHandler::on_reply`, and
+/// can be matched up by the sequence numbers returned here.
+#[derive(Debug, Clone)]
+pub struct WebsocketSender {
+    outgoing: mpsc::UnboundedSender<Message>,
+    seq: Arc<AtomicI64>,
+    close_requested: Arc<AtomicBool>,
+}
+
+impl WebsocketSender {
+    /// Create a sender for a new connection, along with the receiving
+    /// end that the connection's loop forwards to the websocket.
+    pub(crate) fn new() -> (Self, mpsc::UnboundedReceiver<Message>) {
+        let (outgoing, receiver) = mpsc::unbounded_channel();
+        let sender = Self {
+            outgoing,
+            // 1 is used by the authentication challenge
+            seq: Arc::new(AtomicI64::new(2)),
+            close_requested: Arc::new(AtomicBool::new(false)),
+        };
+        (sender, receiver)
+    }
+
+    /// Send an action with the next sequence number, returning that number.
+    ///
+    /// Fails with `ApiError::WebsocketClosed` if the connection is gone.
+    pub fn send_action(&self, action: &str, data: serde_json::Value) -> Result<i64, ApiError> {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let message = json!({
+            "seq": seq,
+            "action": action,
+            "data": data,
+        });
+        self.outgoing
+            .send(Message::Text(message.to_string()))
+            .map_err(|_| ApiError::WebsocketClosed)?;
+        Ok(seq)
+    }
+
+    /// Close the connection, sending a Close frame to the server.
+    ///
+    /// The connection's loop ends and `connect_to_websocket` returns
+    /// `Ok(())`. `connect_to_websocket_with_reconnect` also returns
+    /// `Ok(())` rather than reconnecting.
+    pub fn close(&self) -> Result<(), ApiError> {
+        self.close_requested.store(true, Ordering::Relaxed);
+        self.outgoing
+            .send(Message::Close(None))
+            .map_err(|_| ApiError::WebsocketClosed)
+    }
+
+    /// If `close` has been called on this sender or a clone of it.
+    pub(crate) fn close_requested(&self) -> bool {
+        self.close_requested.load(Ordering::Relaxed)
+    }
+
+    /// Show other users that the logged in user is typing in a
+    /// channel, or in a thread if `parent_id` is the thread's root post.
+    pub fn send_user_typing(
+        &self,
+        channel_id: &str,
+        parent_id: Option<&str>,
+    ) -> Result<i64, ApiError> {
+        self.send_action(
+            "user_typing",
+            json!({
+                "channel_id": channel_id,
+                "parent_id": parent_id.unwrap_or_default(),
+            }),
+        )
+    }
+}
+
+/// Settings for reconnecting to the websocket API after the connection is lost.
+///
+/// The wait before each attempt doubles from `initial_backoff` up
+/// to `max_backoff`, and goes back to `initial_backoff` once a
+/// connection is made.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Number of failed attempts to connect in a row after which
+    /// to give up, or `None` to keep trying forever.
+    pub max_retries: Option<u32>,
+    /// Wait before the first attempt to reconnect.
+    pub initial_backoff: Duration,
+    /// Longest wait between attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    /// Up to 10 attempts, waiting from 1 second up to 1 minute between them.
+    fn default() -> Self {
+        Self {
+            max_retries: Some(10),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Wait before reconnecting after the given number of failed attempts in a row.
+    pub(crate) fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
 }
 
 /// Websocket event names.
@@ -81,6 +590,8 @@ pub mod websocket_event_types {
     pub const PLUGIN_DISABLED: &str = "plugin_disabled";
     pub const PLUGIN_ENABLED: &str = "plugin_enabled";
     pub const PLUGIN_STATUSES_CHANGED: &str = "plugin_statuses_changed";
+    pub const POST_ACKNOWLEDGEMENT_ADDED: &str = "post_acknowledgement_added";
+    pub const POST_ACKNOWLEDGEMENT_REMOVED: &str = "post_acknowledgement_removed";
     pub const POST_DELETED: &str = "post_deleted";
     pub const POST_EDITED: &str = "post_edited";
     pub const POST_UNREAD: &str = "post_unread";
@@ -104,3 +615,539 @@ pub mod websocket_event_types {
     pub const THREAD_FOLLOW_CHANGED: &str = "thread_follow_changed";
     pub const THREAD_READ_CHANGED: &str = "thread_read_changed";
 }
+
+/// What to do with websocket events when the receiver of
+/// `Mattermost::websocket_events` isn't keeping up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backpressure {
+    /// Stop reading from the connection until there's room for the
+    /// event. Keep-alive pings and presence updates are paused too, so
+    /// the server may close the connection if the receiver stalls.
+    #[default]
+    Wait,
+    /// Keep reading, discarding the oldest waiting event to make room.
+    DropOldest,
+}
+
+/// Settings for the channel of `Mattermost::websocket_events`.
+#[derive(Debug, Clone)]
+pub struct WebsocketEventsOptions {
+    /// Number of events that can wait to be received.
+    pub capacity: usize,
+    /// What to do when that many events are waiting.
+    pub backpressure: Backpressure,
+}
+
+impl Default for WebsocketEventsOptions {
+    /// Up to 100 events, waiting for room when full.
+    fn default() -> Self {
+        Self {
+            capacity: 100,
+            backpressure: Backpressure::Wait,
+        }
+    }
+}
+
+/// Handle for a websocket connection whose events are sent to a channel.
+///
+/// Dropping the handle doesn't close the connection; call `close`,
+/// or drop the receiving end of the channel.
+#[derive(Debug)]
+pub struct WebsocketConnectionHandle {
+    pub(crate) sender: WebsocketSender,
+    pub(crate) task: JoinHandle<Result<(), ApiError>>,
+}
+
+impl WebsocketConnectionHandle {
+    /// Handle for sending actions through the connection.
+    pub fn sender(&self) -> &WebsocketSender {
+        &self.sender
+    }
+
+    /// Close the connection, sending a Close frame to the server.
+    ///
+    /// Events already received are still delivered, then the channel closes.
+    pub fn close(&self) -> Result<(), ApiError> {
+        self.sender.close()
+    }
+
+    /// Wait for the connection to end, returning the error that ended it, if any.
+    pub async fn join(self) -> Result<(), ApiError> {
+        match self.task.await {
+            Ok(result) => result,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+}
+
+/// Handler that sends events into the channel of `Mattermost::websocket_events`,
+/// closing the connection once the receiver is dropped.
+pub(crate) struct ChannelHandler {
+    events: EventSink,
+    sender: WebsocketSender,
+}
+
+enum EventSink {
+    Bounded(mpsc::Sender<WebsocketEvent>),
+    /// To a task that drops the oldest buffered events when full.
+    Buffered(mpsc::UnboundedSender<WebsocketEvent>),
+}
+
+impl ChannelHandler {
+    /// Create a handler for a connection, along with the receiving end
+    /// of its channel.
+    pub(crate) fn new(
+        sender: WebsocketSender,
+        options: &WebsocketEventsOptions,
+    ) -> (Self, mpsc::Receiver<WebsocketEvent>) {
+        let capacity = options.capacity.max(1);
+        let (events, receiver) = match options.backpressure {
+            Backpressure::Wait => {
+                let (tx, rx) = mpsc::channel(capacity);
+                (EventSink::Bounded(tx), rx)
+            }
+            Backpressure::DropOldest => {
+                let (tx, buffered) = mpsc::unbounded_channel();
+                let (out, rx) = mpsc::channel(1);
+                tokio::spawn(forward_dropping_oldest(buffered, out, capacity));
+                (EventSink::Buffered(tx), rx)
+            }
+        };
+        (Self { events, sender }, receiver)
+    }
+
+    /// Wait until the receiving end of the channel is dropped.
+    pub(crate) async fn receiver_dropped(&self) {
+        match &self.events {
+            EventSink::Bounded(tx) => tx.closed().await,
+            EventSink::Buffered(tx) => tx.closed().await,
+        }
+    }
+
+    /// Close the connection, if it's still open.
+    pub(crate) fn close(&self) {
+        // fails only if the connection is already gone
+        let _ = self.sender.close();
+    }
+}
+
+#[async_trait]
+impl WebsocketHandler for ChannelHandler {
+    async fn callback(&self, message: WebsocketEvent) {
+        let delivered = match &self.events {
+            EventSink::Bounded(tx) => tx.send(message).await.is_ok(),
+            EventSink::Buffered(tx) => tx.send(message).is_ok(),
+        };
+        if !delivered {
+            debug!("Websocket event receiver was dropped; closing the connection");
+            self.close();
+        }
+    }
+}
+
+/// Forward events to `out`, keeping up to `capacity` of them waiting
+/// and discarding the oldest when more arrive.
+async fn forward_dropping_oldest(
+    mut events: mpsc::UnboundedReceiver<WebsocketEvent>,
+    out: mpsc::Sender<WebsocketEvent>,
+    capacity: usize,
+) {
+    let mut waiting = VecDeque::with_capacity(capacity);
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+                if waiting.len() == capacity {
+                    debug!("Websocket event receiver is behind; dropping the oldest event");
+                    waiting.pop_front();
+                }
+                waiting.push_back(event);
+            },
+            permit = out.reserve(), if !waiting.is_empty() => {
+                let Ok(permit) = permit else {
+                    // the receiver was dropped
+                    return;
+                };
+                if let Some(event) = waiting.pop_front() {
+                    permit.send(event);
+                }
+            },
+            () = out.closed(), if waiting.is_empty() => {
+                return;
+            },
+        }
+    }
+    // the connection ended; deliver what's left
+    for event in waiting {
+        if out.send(event).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_backoff_doubles_up_to_max() {
+        let policy = ReconnectPolicy {
+            max_retries: None,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        let waits: Vec<u128> = (0..7).map(|n| policy.backoff(n).as_millis()).collect();
+        assert_eq!(waits, vec![100, 100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    fn event(json: &str) -> WebsocketEvent {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn replies_are_told_apart_from_events_by_their_fields() {
+        let reply = IncomingMessage::parse(
+            r#"{"status": "FAIL", "seq_reply": 1, "error": {"id": "api.web_socket_router.not_authenticated.app_error",
+                "message": "not authenticated", "status_code": 401}}"#,
+        )
+        .unwrap();
+        let IncomingMessage::Reply(reply) = reply else {
+            panic!("expected a reply");
+        };
+        assert!(!reply.is_ok());
+        assert_eq!(reply.seq_reply, 1);
+        assert_eq!(reply.error.unwrap().status_code, 401);
+
+        let IncomingMessage::Reply(reply) =
+            IncomingMessage::parse(r#"{"status": "OK", "seq_reply": 3, "data": {"u1": "online"}}"#)
+                .unwrap()
+        else {
+            panic!("expected a reply");
+        };
+        assert!(reply.is_ok());
+        assert_eq!(reply.data.unwrap()["u1"], "online");
+
+        // a post mentioning seq_reply is still an event
+        let posted = IncomingMessage::parse(
+            r#"{"event": "posted", "data": {"message": "what is \"seq_reply\"?"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c",
+                "team_id": ""}, "seq": 4}"#,
+        )
+        .unwrap();
+        assert!(matches!(posted, IncomingMessage::Event(e) if e.event == "posted"));
+    }
+
+    #[test]
+    fn broadcasts_from_different_server_versions_are_read() {
+        // 5.37
+        let hello = event(
+            r#"{"event": "hello", "data": {"server_version": "5.37.0.5.37.0.5b7b4c1.false"},
+                "broadcast": {"omit_users": null, "user_id": "u1", "channel_id": "",
+                "team_id": ""}, "seq": 0}"#,
+        );
+        assert_eq!(hello.broadcast.user_id.as_deref(), Some("u1"));
+        assert_eq!(hello.broadcast.connection_id, "");
+        // 9.5
+        let hello = event(
+            r#"{"event": "hello", "data": {"connection_id": "conn1", "server_hostname": "",
+                "server_version": "9.5.0.9.5.0.8a2c1d5.true"},
+                "broadcast": {"omit_users": null, "user_id": "u1", "channel_id": "",
+                "team_id": "", "connection_id": "conn1", "omit_connection_id": ""},
+                "seq": 0}"#,
+        );
+        assert_eq!(hello.broadcast.connection_id, "conn1");
+        // config_changed without channel or team
+        let config = event(
+            r#"{"event": "config_changed", "data": {"config": {}},
+                "broadcast": {"omit_users": null, "user_id": ""}, "seq": 4}"#,
+        );
+        assert_eq!(config.broadcast.channel_id, "");
+        assert_eq!(config.broadcast.team_id, "");
+        // 9.5 posted, omitting the connection that made the post
+        let posted = event(
+            r#"{"event": "posted", "data": {"channel_display_name": "Town Square",
+                "channel_name": "town-square", "channel_type": "O",
+                "post": "{\"id\":\"p1\",\"create_at\":1,\"update_at\":1,\"delete_at\":0,\"user_id\":\"u1\",\"channel_id\":\"c1\",\"message\":\"hi\",\"type\":\"\"}",
+                "sender_name": "@alice", "set_online": true, "team_id": "t1"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c1",
+                "team_id": "", "connection_id": "", "omit_connection_id": "conn2"},
+                "seq": 5}"#,
+        );
+        assert_eq!(posted.broadcast.omit_connection_id, "conn2");
+        assert!(matches!(
+            posted.parsed_data().unwrap(),
+            WebsocketEventData::Posted(_)
+        ));
+    }
+
+    #[test]
+    fn posted_event_decodes_nested_post() {
+        let event = event(
+            r#"{"event": "posted", "data": {"channel_display_name": "Town Square",
+                "channel_name": "town-square", "channel_type": "O",
+                "mentions": "[\"u2\"]",
+                "post": "{\"id\":\"p1\",\"create_at\":1700000000000,\"update_at\":1700000000000,\"edit_at\":0,\"delete_at\":0,\"is_pinned\":false,\"user_id\":\"u1\",\"channel_id\":\"c1\",\"root_id\":\"\",\"original_id\":\"\",\"message\":\"hi @bob\",\"type\":\"\",\"props\":{},\"hashtags\":\"\",\"pending_post_id\":\"u1:1700000000000\",\"reply_count\":0,\"metadata\":{}}",
+                "sender_name": "@alice", "set_online": true, "team_id": "t1"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c1",
+                "team_id": ""}, "seq": 3}"#,
+        );
+        let WebsocketEventData::Posted(posted) = event.parsed_data().unwrap() else {
+            panic!("Expected a posted event");
+        };
+        assert_eq!(posted.post.id, "p1");
+        assert_eq!(posted.post.message, "hi @bob");
+        assert_eq!(posted.channel_type, Some(ChannelType::Open));
+        assert_eq!(posted.sender_name, "@alice");
+        assert_eq!(posted.mentions, vec!["u2"]);
+    }
+
+    #[test]
+    fn posted_event_without_mentions() {
+        let event = event(
+            r#"{"event": "posted", "data": {"channel_display_name": "@bob",
+                "channel_name": "u1__u2", "channel_type": "D",
+                "post": "{\"id\":\"p2\",\"create_at\":1,\"update_at\":1,\"delete_at\":0,\"user_id\":\"u1\",\"channel_id\":\"c2\",\"message\":\"dm\",\"type\":\"\"}",
+                "sender_name": "@alice", "team_id": ""},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c2",
+                "team_id": ""}, "seq": 4}"#,
+        );
+        let WebsocketEventData::Posted(posted) = event.parsed_data().unwrap() else {
+            panic!("Expected a posted event");
+        };
+        assert!(posted.mentions.is_empty());
+        assert_eq!(posted.channel_type, Some(ChannelType::Direct));
+    }
+
+    #[test]
+    fn post_edited_and_deleted_events() {
+        let edited = event(
+            r#"{"event": "post_edited", "data": {"post": "{\"id\":\"p1\",\"create_at\":1,\"update_at\":2,\"edit_at\":2,\"delete_at\":0,\"user_id\":\"u1\",\"channel_id\":\"c1\",\"message\":\"edited\",\"type\":\"\"}"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c1",
+                "team_id": ""}, "seq": 5}"#,
+        );
+        let WebsocketEventData::PostEdited(post) = edited.parsed_data().unwrap() else {
+            panic!("Expected a post_edited event");
+        };
+        assert_eq!(post.edit_at, 2);
+
+        let deleted = event(
+            r#"{"event": "post_deleted", "data": {"delete_by": "u9", "post": "{\"id\":\"p1\",\"create_at\":1,\"update_at\":3,\"delete_at\":3,\"user_id\":\"u1\",\"channel_id\":\"c1\",\"message\":\"\",\"type\":\"\"}"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c1",
+                "team_id": ""}, "seq": 6}"#,
+        );
+        let WebsocketEventData::PostDeleted(deleted) = deleted.parsed_data().unwrap() else {
+            panic!("Expected a post_deleted event");
+        };
+        assert_eq!(deleted.post.delete_at, 3);
+        assert_eq!(deleted.delete_by.as_deref(), Some("u9"));
+    }
+
+    #[test]
+    fn ephemeral_message_event() {
+        let ephemeral = event(
+            r#"{"event": "ephemeral_message", "data": {"post": "{\"id\":\"p2\",\"create_at\":1,\"update_at\":1,\"delete_at\":0,\"user_id\":\"bot\",\"channel_id\":\"c1\",\"message\":\"only you can see this\",\"type\":\"system_ephemeral\"}"},
+                "broadcast": {"omit_users": null, "user_id": "u1", "channel_id": "",
+                "team_id": ""}, "seq": 7}"#,
+        );
+        let WebsocketEventData::EphemeralMessage(post) = ephemeral.parsed_data().unwrap() else {
+            panic!("Expected an ephemeral_message event");
+        };
+        assert_eq!(post.message, "only you can see this");
+    }
+
+    #[test]
+    fn typing_status_and_reaction_events() {
+        let typing = event(
+            r#"{"event": "typing", "data": {"parent_id": "", "user_id": "u1"},
+                "broadcast": {"omit_users": {"u1": true}, "user_id": "", "channel_id": "c1",
+                "team_id": ""}, "seq": 7}"#,
+        );
+        assert!(matches!(
+            typing.parsed_data().unwrap(),
+            WebsocketEventData::Typing(TypingEvent { user_id, parent_id })
+                if user_id == "u1" && parent_id.is_empty()
+        ));
+
+        let status = event(
+            r#"{"event": "status_change", "data": {"status": "away", "user_id": "u1"},
+                "broadcast": {"omit_users": null, "user_id": "u1", "channel_id": "",
+                "team_id": ""}, "seq": 8}"#,
+        );
+        assert!(matches!(
+            status.parsed_data().unwrap(),
+            WebsocketEventData::StatusChange(StatusChangeEvent {
+                status: PresenceStatus::Away,
+                ..
+            })
+        ));
+
+        let reaction = event(
+            r#"{"event": "reaction_added", "data": {"reaction": "{\"user_id\":\"u1\",\"post_id\":\"p1\",\"emoji_name\":\"+1\",\"create_at\":1700000000000,\"update_at\":1700000000000,\"delete_at\":0,\"remote_id\":\"\",\"channel_id\":\"c1\"}"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c1",
+                "team_id": ""}, "seq": 9}"#,
+        );
+        let WebsocketEventData::ReactionAdded(reaction) = reaction.parsed_data().unwrap() else {
+            panic!("Expected a reaction_added event");
+        };
+        assert_eq!(reaction.emoji_name, "+1");
+        assert_eq!(reaction.post_id, "p1");
+    }
+
+    #[test]
+    fn post_acknowledgement_events() {
+        let added = event(
+            r#"{"event": "post_acknowledgement_added", "data": {"acknowledgement": "{\"user_id\":\"u1\",\"post_id\":\"p1\",\"acknowledged_at\":1700000000000}"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c1",
+                "team_id": ""}, "seq": 10}"#,
+        );
+        let WebsocketEventData::PostAcknowledgementAdded(ack) = added.parsed_data().unwrap() else {
+            panic!("Expected a post_acknowledgement_added event");
+        };
+        assert_eq!(ack.post_id, "p1");
+        assert_eq!(ack.acknowledged_at, 1700000000000);
+
+        let removed = event(
+            r#"{"event": "post_acknowledgement_removed", "data": {"acknowledgement": "{\"user_id\":\"u1\",\"post_id\":\"p1\",\"acknowledged_at\":0}"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c1",
+                "team_id": ""}, "seq": 11}"#,
+        );
+        assert!(matches!(
+            removed.parsed_data().unwrap(),
+            WebsocketEventData::PostAcknowledgementRemoved(PostAcknowledgement { user_id, .. })
+                if user_id == "u1"
+        ));
+    }
+
+    #[test]
+    fn untyped_events_fall_back_to_value() {
+        let hello = event(
+            r#"{"event": "hello", "data": {"server_version": "7.8.0"},
+                "broadcast": {"omit_users": null, "user_id": "u1", "channel_id": "",
+                "team_id": ""}, "seq": 0}"#,
+        );
+        let WebsocketEventData::Other(data) = hello.parsed_data().unwrap() else {
+            panic!("Expected an untyped event");
+        };
+        assert_eq!(data["server_version"], "7.8.0");
+    }
+
+    #[test]
+    fn user_updated_for_other_users_omits_private_fields() {
+        let event = event(
+            r#"{"event": "user_updated", "data": {"user": {
+                "id": "u1", "create_at": 1700000000000, "update_at": 1700000500000,
+                "delete_at": 1700000500000, "username": "departed", "auth_data": "",
+                "nickname": "", "position": "", "roles": "system_user",
+                "locale": "en", "is_bot": false}},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "",
+                "team_id": ""}, "seq": 4}"#,
+        );
+        let Some(Ok(UserEvent::Updated(user))) = event.user_event() else {
+            panic!("Expected a user_updated event");
+        };
+        assert_eq!(user.username, "departed");
+        assert!(user.is_deactivated());
+        assert_eq!(user.email, None);
+        assert_eq!(user.first_name, None);
+        assert!(user.notify_props.is_none());
+    }
+
+    #[test]
+    fn user_updated_for_the_user_themselves_has_private_fields() {
+        let event = event(
+            r#"{"event": "user_updated", "data": {"user": {
+                "id": "u1", "create_at": 1700000000000, "update_at": 1700000500000,
+                "delete_at": 0, "username": "me", "email": "me@example.com",
+                "first_name": "Me", "last_name": "Myself", "nickname": "",
+                "position": "", "roles": "system_user", "locale": "en",
+                "notify_props": {"desktop": "mention", "email": "true"},
+                "last_password_update": 1700000000000, "auth_service": "",
+                "timezone": {"automaticTimezone": "", "manualTimezone": "",
+                "useAutomaticTimezone": "true"}}},
+                "broadcast": {"omit_users": null, "user_id": "u1", "channel_id": "",
+                "team_id": ""}, "seq": 5}"#,
+        );
+        let Some(Ok(UserEvent::Updated(user))) = event.user_event() else {
+            panic!("Expected a user_updated event");
+        };
+        assert!(!user.is_deactivated());
+        assert_eq!(user.email.as_deref(), Some("me@example.com"));
+        assert_eq!(user.notify_props.unwrap()["desktop"], "mention");
+    }
+
+    #[test]
+    fn membership_events_are_typed() {
+        let added = event(
+            r#"{"event": "user_added", "data": {"team_id": "t1", "user_id": "u1"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c1",
+                "team_id": ""}, "seq": 6}"#,
+        );
+        assert!(matches!(
+            added.user_event(),
+            Some(Ok(UserEvent::AddedToChannel { user_id, channel_id, team_id }))
+                if user_id == "u1" && channel_id == "c1" && team_id == "t1"
+        ));
+
+        let removed_for_others = event(
+            r#"{"event": "user_removed", "data": {"remover_id": "u2", "user_id": "u1"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "c1",
+                "team_id": ""}, "seq": 7}"#,
+        );
+        let removed_for_self = event(
+            r#"{"event": "user_removed", "data": {"channel_id": "c1", "remover_id": "u2"},
+                "broadcast": {"omit_users": null, "user_id": "u1", "channel_id": "",
+                "team_id": ""}, "seq": 8}"#,
+        );
+        for removed in [removed_for_others, removed_for_self] {
+            assert!(matches!(
+                removed.user_event(),
+                Some(Ok(UserEvent::RemovedFromChannel { user_id, channel_id, remover_id }))
+                    if user_id == "u1" && channel_id == "c1" && remover_id.as_deref() == Some("u2")
+            ));
+        }
+
+        let new_user = event(
+            r#"{"event": "new_user", "data": {"user_id": "u3"},
+                "broadcast": {"omit_users": null, "user_id": "", "channel_id": "",
+                "team_id": ""}, "seq": 9}"#,
+        );
+        assert!(matches!(
+            new_user.user_event(),
+            Some(Ok(UserEvent::New { user_id })) if user_id == "u3"
+        ));
+
+        let typing = event(
+            r#"{"event": "typing", "data": {}, "broadcast": {"omit_users": null,
+                "user_id": "", "channel_id": "c1", "team_id": ""}, "seq": 10}"#,
+        );
+        assert!(typing.user_event().is_none());
+    }
+
+    #[tokio::test]
+    async fn sender_numbers_actions_after_the_authentication_challenge() {
+        let (sender, mut outgoing) = WebsocketSender::new();
+        let clone = sender.clone();
+        assert_eq!(sender.send_user_typing("c", None).unwrap(), 2);
+        assert_eq!(clone.send_action("get_statuses", json!({})).unwrap(), 3);
+
+        let first: serde_json::Value =
+            serde_json::from_str(outgoing.recv().await.unwrap().to_text().unwrap()).unwrap();
+        assert_eq!(
+            first,
+            json!({"seq": 2, "action": "user_typing",
+                   "data": {"channel_id": "c", "parent_id": ""}})
+        );
+        let second: serde_json::Value =
+            serde_json::from_str(outgoing.recv().await.unwrap().to_text().unwrap()).unwrap();
+        assert_eq!(second["seq"], 3);
+
+        drop(outgoing);
+        assert!(matches!(
+            sender.send_action("get_statuses", json!({})),
+            Err(ApiError::WebsocketClosed)
+        ));
+    }
+}
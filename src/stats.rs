@@ -0,0 +1,224 @@
+//! Opt-in request statistics, collected per endpoint.
+//!
+//! Enable collection with [`Mattermost::with_stats`], then call
+//! [`ClientStats::snapshot`] on [`Mattermost::stats`] to see which
+//! calls to the instance API take the most time.
+//!
+//! [`Mattermost::with_stats`]: ../client/struct.Mattermost.html#method.with_stats
+//! [`Mattermost::stats`]: ../client/struct.Mattermost.html#method.stats
+
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+
+/// Number of most recent latencies kept per endpoint for percentiles.
+const MAX_LATENCY_SAMPLES: usize = 1000;
+
+/// Collector of request counts, errors, latencies, and payload sizes.
+#[derive(Debug, Default)]
+pub struct ClientStats {
+    endpoints: Mutex<HashMap<(String, String), EndpointSamples>>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointSamples {
+    requests: u64,
+    errors: u64,
+    bytes_received: u64,
+    latencies: VecDeque<Duration>,
+}
+
+/// Statistics for all endpoints that have been requested.
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsReport {
+    /// Sorted by method and endpoint.
+    pub endpoints: Vec<EndpointReport>,
+}
+
+/// Statistics for a single endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EndpointReport {
+    /// HTTP method, e.g. "GET".
+    pub method: String,
+    /// The endpoint with ids and names replaced by placeholders,
+    /// e.g. "channels/{channel_id}/posts".
+    ///
+    /// Requests made with `Mattermost::query` and the like, to paths
+    /// built by the caller, have their placeholders guessed from the
+    /// path instead, e.g. "channels/{id}/posts".
+    pub endpoint: String,
+    /// Number of requests made.
+    pub requests: u64,
+    /// Number of requests that failed or got a non-success status code.
+    pub errors: u64,
+    /// Median latency in milliseconds.
+    pub p50_ms: f64,
+    /// 95th percentile latency in milliseconds.
+    pub p95_ms: f64,
+    /// Total size of the response bodies read.
+    pub bytes_received: u64,
+}
+
+impl ClientStats {
+    /// Create an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a finished request to an endpoint template.
+    pub(crate) fn record(&self, method: &str, endpoint: &str, latency: Duration, is_error: bool) {
+        let key = (method.to_owned(), endpoint.to_owned());
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let samples = endpoints.entry(key).or_default();
+        samples.requests += 1;
+        if is_error {
+            samples.errors += 1;
+        }
+        if samples.latencies.len() == MAX_LATENCY_SAMPLES {
+            samples.latencies.pop_front();
+        }
+        samples.latencies.push_back(latency);
+    }
+
+    /// Record response body bytes read from an endpoint template.
+    pub(crate) fn record_bytes(&self, method: &str, endpoint: &str, bytes: u64) {
+        let key = (method.to_owned(), endpoint.to_owned());
+        let mut endpoints = self.endpoints.lock().unwrap();
+        endpoints.entry(key).or_default().bytes_received += bytes;
+    }
+
+    /// Report of the statistics collected so far.
+    ///
+    /// Percentiles are over the most recent requests to each endpoint.
+    pub fn snapshot(&self) -> StatsReport {
+        let endpoints = self.endpoints.lock().unwrap();
+        let mut reports: Vec<EndpointReport> = endpoints
+            .iter()
+            .map(|((method, endpoint), samples)| {
+                let mut latencies: Vec<Duration> = samples.latencies.iter().copied().collect();
+                latencies.sort();
+                EndpointReport {
+                    method: method.clone(),
+                    endpoint: endpoint.clone(),
+                    requests: samples.requests,
+                    errors: samples.errors,
+                    p50_ms: percentile_ms(&latencies, 50),
+                    p95_ms: percentile_ms(&latencies, 95),
+                    bytes_received: samples.bytes_received,
+                }
+            })
+            .collect();
+        reports.sort_by(|a, b| (&a.endpoint, &a.method).cmp(&(&b.endpoint, &b.method)));
+        StatsReport { endpoints: reports }
+    }
+
+    /// Clear all collected statistics.
+    pub fn reset(&self) {
+        self.endpoints.lock().unwrap().clear();
+    }
+}
+
+/// Nearest-rank percentile of sorted latencies, in milliseconds.
+fn percentile_ms(sorted: &[Duration], percentile: usize) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1].as_secs_f64() * 1000.0
+}
+
+/// Whether a path segment looks like a Mattermost id.
+fn is_id(segment: &str) -> bool {
+    segment.len() == 26
+        && segment
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+}
+
+/// Replace the ids and names in an API path with placeholders,
+/// so that requests to the same endpoint are grouped together.
+///
+/// Only for paths the crate didn't build, which have no template.
+pub(crate) fn endpoint_template(path: &str) -> String {
+    let mut previous = "";
+    path.trim_matches('/')
+        .split('/')
+        .map(|segment| {
+            let replaced = match previous {
+                "name" => "{name}",
+                "username" => "{username}",
+                "email" => "{email}",
+                "invite" => "{invite_id}",
+                _ if is_id(segment) => "{id}",
+                _ => segment,
+            };
+            previous = segment;
+            replaced
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_are_normalized_to_templates() {
+        let id = "4xp9fdt77pncbef59f4k1qe83o";
+        let cases = [
+            (format!("channels/{}/posts", id), "channels/{id}/posts"),
+            (
+                format!("users/{}/teams/{}/unread", id, id),
+                "users/{id}/teams/{id}/unread",
+            ),
+            ("teams/name/best-team".to_owned(), "teams/name/{name}"),
+            (
+                format!("teams/{}/channels/name/town-square", id),
+                "teams/{id}/channels/name/{name}",
+            ),
+            (
+                "users/username/alice".to_owned(),
+                "users/username/{username}",
+            ),
+            ("teams/invite/abc123".to_owned(), "teams/invite/{invite_id}"),
+            ("users/me".to_owned(), "users/me"),
+            ("/system/ping/".to_owned(), "system/ping"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(endpoint_template(&path), expected, "for {}", path);
+        }
+    }
+
+    #[test]
+    fn snapshot_reports_counts_and_percentiles() {
+        let stats = ClientStats::new();
+        for ms in 1..=100 {
+            stats.record(
+                "GET",
+                "channels/{channel_id}/posts",
+                Duration::from_millis(ms),
+                ms > 98,
+            );
+            stats.record_bytes("GET", "channels/{channel_id}/posts", 10);
+        }
+        stats.record("GET", "users/me", Duration::from_millis(5), false);
+
+        let report = stats.snapshot();
+        assert_eq!(report.endpoints.len(), 2);
+        let posts = &report.endpoints[0];
+        assert_eq!(posts.endpoint, "channels/{channel_id}/posts");
+        assert_eq!(posts.requests, 100);
+        assert_eq!(posts.errors, 2);
+        assert_eq!(posts.bytes_received, 1000);
+        assert_eq!(posts.p50_ms, 50.0);
+        assert_eq!(posts.p95_ms, 95.0);
+        assert_eq!(report.endpoints[1].p95_ms, 5.0);
+
+        stats.reset();
+        assert!(stats.snapshot().endpoints.is_empty());
+    }
+}
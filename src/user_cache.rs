@@ -0,0 +1,135 @@
+//! Opt-in cache of user lookups, enabled with `Mattermost::with_user_cache`.
+
+use crate::models::UserInformation;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+/// Least recently used cache of users, by id and by username.
+#[derive(Debug)]
+pub(crate) struct UserCache {
+    capacity: usize,
+    inner: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// Incremented on every use, to order the entries by recency.
+    tick: u64,
+    /// Users by id, with the tick they were last used at.
+    users: HashMap<String, (UserInformation, u64)>,
+    /// User ids by the tick they were last used at, oldest first.
+    recency: BTreeMap<u64, String>,
+    /// User ids by username.
+    usernames: HashMap<String, String>,
+}
+
+impl UserCache {
+    /// Create a cache holding at most `capacity` users.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Get a user by their id, marking them as recently used.
+    pub(crate) fn get(&self, user_id: &str) -> Option<UserInformation> {
+        self.inner.lock().unwrap().touch(user_id)
+    }
+
+    /// Get a user by their username, marking them as recently used.
+    pub(crate) fn get_by_username(&self, username: &str) -> Option<UserInformation> {
+        let mut entries = self.inner.lock().unwrap();
+        let user_id = entries.usernames.get(username)?.clone();
+        entries.touch(&user_id)
+    }
+
+    /// Add or replace a user, evicting the least recently used
+    /// users if the cache is full.
+    pub(crate) fn insert(&self, user: UserInformation) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.inner.lock().unwrap();
+        entries.remove(&user.id);
+        entries.tick += 1;
+        let tick = entries.tick;
+        entries.recency.insert(tick, user.id.clone());
+        entries
+            .usernames
+            .insert(user.username.clone(), user.id.clone());
+        entries.users.insert(user.id.clone(), (user, tick));
+        while entries.users.len() > self.capacity {
+            let Some((_, oldest)) = entries.recency.pop_first() else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+    }
+
+    /// Remove all users.
+    pub(crate) fn clear(&self) {
+        *self.inner.lock().unwrap() = Entries::default();
+    }
+}
+
+impl Entries {
+    fn touch(&mut self, user_id: &str) -> Option<UserInformation> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (user, used_at) = self.users.get_mut(user_id)?;
+        self.recency.remove(used_at);
+        *used_at = tick;
+        self.recency.insert(tick, user_id.to_owned());
+        Some(user.clone())
+    }
+
+    fn remove(&mut self, user_id: &str) {
+        if let Some((user, used_at)) = self.users.remove(user_id) {
+            self.recency.remove(&used_at);
+            if self.usernames.get(&user.username).map(String::as_str) == Some(user_id) {
+                self.usernames.remove(&user.username);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: &str, username: &str) -> UserInformation {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "username": username, "create_at": 0, "update_at": 0, "delete_at": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn least_recently_used_users_are_evicted() {
+        let cache = UserCache::new(2);
+        cache.insert(user("a", "alice"));
+        cache.insert(user("b", "bob"));
+        assert!(cache.get("a").is_some());
+        cache.insert(user("c", "carol"));
+
+        assert!(cache.get("b").is_none());
+        assert!(cache.get_by_username("bob").is_none());
+        assert_eq!(cache.get_by_username("alice").unwrap().id, "a");
+        assert_eq!(cache.get("c").unwrap().username, "carol");
+    }
+
+    #[test]
+    fn replacing_a_user_updates_their_username() {
+        let cache = UserCache::new(2);
+        cache.insert(user("a", "alice"));
+        cache.insert(user("a", "alicia"));
+
+        assert!(cache.get_by_username("alice").is_none());
+        assert_eq!(cache.get_by_username("alicia").unwrap().id, "a");
+        cache.clear();
+        assert!(cache.get("a").is_none());
+    }
+}